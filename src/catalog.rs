@@ -0,0 +1,174 @@
+//! Mapping of ontology IRIs to local files, for resolving `owl:imports`.
+use quick_xml::{Reader, events::Event};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    horned_oxi::errors::HornedOxiError,
+    store::{path_type, uncompressed_path},
+};
+
+/// Name of the catalog file written by Protégé next to an ontology.
+pub const CATALOG_FILE: &str = "catalog-v001.xml";
+
+/// Resolves IRIs to local files, first through explicit mappings such as
+/// those of an XML catalog, then by looking for a file named after the last
+/// segment of the IRI in each mapped directory.
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    mappings: HashMap<String, PathBuf>,
+    directories: Vec<PathBuf>,
+}
+
+impl Catalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read the `uri` entries of an OASIS XML catalog. Relative paths are
+    /// resolved against the directory of the catalog.
+    pub fn from_file(path: &Path) -> Result<Self, HornedOxiError> {
+        let mut catalog = Self::new();
+        catalog.read_catalog(path)?;
+        Ok(catalog)
+    }
+
+    /// The catalog next to `ontology`, if there is one, with the directory
+    /// of `ontology` mapped.
+    pub fn for_ontology(ontology: &Path) -> Result<Self, HornedOxiError> {
+        let directory = ontology.parent().unwrap_or(Path::new("."));
+        let mut catalog = Self::new().with_directory(directory);
+        let file = directory.join(CATALOG_FILE);
+        if file.is_file() {
+            catalog.read_catalog(&file)?;
+        }
+        Ok(catalog)
+    }
+
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directories.push(directory.into());
+        self
+    }
+
+    pub fn insert(&mut self, iri: impl Into<String>, path: impl Into<PathBuf>) {
+        self.mappings.insert(iri.into(), path.into());
+    }
+
+    pub fn resolve(&self, iri: &str) -> Option<PathBuf> {
+        if let Some(path) = self.mappings.get(iri) {
+            return Some(path.clone());
+        }
+        let name = iri.trim_end_matches(['#', '/']).rsplit(['/', '#']).next()?;
+        self.directories.iter().find_map(|directory| {
+            let direct = directory.join(name);
+            if direct.is_file() && path_type(&direct).is_some() {
+                return Some(direct);
+            }
+            std::fs::read_dir(directory)
+                .ok()?
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .find(|path| {
+                    uncompressed_path(path)
+                        .file_stem()
+                        .is_some_and(|stem| stem == name)
+                        && path_type(path).is_some()
+                })
+        })
+    }
+
+    fn read_catalog(&mut self, path: &Path) -> Result<(), HornedOxiError> {
+        let directory = path.parent().unwrap_or(Path::new("."));
+        let mut reader = Reader::from_file(path)?;
+        let mut buf = Vec::new();
+        // `xml:base` of the enclosing `group` elements.
+        let mut bases: Vec<PathBuf> = vec![directory.to_path_buf()];
+        loop {
+            match reader.read_event_into(&mut buf)? {
+                Event::Start(e) if e.local_name().as_ref() == b"group" => {
+                    let base = match e.try_get_attribute("xml:base")? {
+                        Some(base) => bases
+                            .last()
+                            .unwrap()
+                            .join(local_path(&base.unescape_value()?)),
+                        None => bases.last().unwrap().clone(),
+                    };
+                    bases.push(base);
+                }
+                Event::End(e) if e.local_name().as_ref() == b"group" => {
+                    bases.pop();
+                }
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"uri" => {
+                    if let (Some(name), Some(uri)) =
+                        (e.try_get_attribute("name")?, e.try_get_attribute("uri")?)
+                    {
+                        let path = bases
+                            .last()
+                            .unwrap()
+                            .join(local_path(&uri.unescape_value()?));
+                        self.insert(name.unescape_value()?.to_string(), path);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        Ok(())
+    }
+}
+
+/// The file path of a catalog `uri`, which may be a `file:` IRI.
+fn local_path(uri: &str) -> PathBuf {
+    let path = uri
+        .strip_prefix("file://")
+        .or_else(|| uri.strip_prefix("file:"))
+        .unwrap_or(uri);
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn test_directory_mapping() {
+        let catalog = Catalog::new().with_directory("data/owl-functional/withimport");
+        assert_eq!(
+            catalog.resolve("http://www.example.com/other-property"),
+            Some(PathBuf::from(
+                "data/owl-functional/withimport/other-property.ofn"
+            ))
+        );
+        assert_eq!(catalog.resolve("http://www.example.com/missing"), None);
+    }
+
+    #[test]
+    fn test_catalog_file() {
+        let directory = TempDir::new("catalog");
+        let path = directory.join(CATALOG_FILE);
+        std::fs::write(
+            &path,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<catalog prefer="public" xmlns="urn:oasis:names:tc:entity:xmlns:xml:catalog">
+    <uri id="Imports Wizard Entry" name="http://www.example.com/a" uri="a.owl"/>
+    <group id="Folder Repository" prefer="public" xml:base="modules/">
+        <uri id="Automatically generated entry" name="http://www.example.com/b" uri="b.ttl"/>
+    </group>
+</catalog>"#,
+        )
+        .unwrap();
+        let catalog = Catalog::from_file(&path).unwrap();
+        assert_eq!(
+            catalog.resolve("http://www.example.com/a"),
+            Some(directory.join("a.owl"))
+        );
+        assert_eq!(
+            catalog.resolve("http://www.example.com/b"),
+            Some(directory.join("modules").join("b.ttl"))
+        );
+    }
+}
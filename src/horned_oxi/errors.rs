@@ -1,8 +1,10 @@
-use std::{error::Error, panic::Location};
+use std::{error::Error, fmt, panic::Location};
 
 use horned_owl::error::HornedError;
 use oxigraph::{
+    io::RdfSyntaxError,
     model::IriParseError,
+    sparql::{QueryEvaluationError, SparqlSyntaxError, UpdateEvaluationError},
     store::{LoaderError, StorageError},
 };
 
@@ -14,6 +16,13 @@ pub enum HornedOxiErrorKind {
     IOError(std::io::Error),
     LoaderError(LoaderError),
     IriParseError(IriParseError),
+    JsonError(serde_json::Error),
+    XmlError(quick_xml::Error),
+    RdfSyntaxError(RdfSyntaxError),
+    ZipError(zip::result::ZipError),
+    SparqlSyntaxError(SparqlSyntaxError),
+    QueryEvaluationError(QueryEvaluationError),
+    UpdateEvaluationError(UpdateEvaluationError),
 }
 
 #[derive(Debug)]
@@ -22,12 +31,72 @@ pub struct HornedOxiError {
     location: &'static Location<'static>,
 }
 
+impl HornedOxiError {
+    pub fn kind(&self) -> &HornedOxiErrorKind {
+        &self.inner
+    }
+
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+}
+
+impl fmt::Display for HornedOxiErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HornedOxiErrorKind::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            HornedOxiErrorKind::HornedError(e) => write!(f, "Horned-OWL error: {}", e),
+            HornedOxiErrorKind::OxigraphError(e) => write!(f, "Oxigraph error: {}", e),
+            HornedOxiErrorKind::IOError(e) => write!(f, "IO error: {}", e),
+            HornedOxiErrorKind::LoaderError(e) => write!(f, "Loader error: {}", e),
+            HornedOxiErrorKind::IriParseError(e) => write!(f, "IRI parse error: {}", e),
+            HornedOxiErrorKind::JsonError(e) => write!(f, "JSON error: {}", e),
+            HornedOxiErrorKind::XmlError(e) => write!(f, "XML error: {}", e),
+            HornedOxiErrorKind::RdfSyntaxError(e) => write!(f, "RDF syntax error: {}", e),
+            HornedOxiErrorKind::ZipError(e) => write!(f, "Zip error: {}", e),
+            HornedOxiErrorKind::SparqlSyntaxError(e) => write!(f, "SPARQL syntax error: {}", e),
+            HornedOxiErrorKind::QueryEvaluationError(e) => {
+                write!(f, "Query evaluation error: {}", e)
+            }
+            HornedOxiErrorKind::UpdateEvaluationError(e) => {
+                write!(f, "Update evaluation error: {}", e)
+            }
+        }
+    }
+}
+
+impl fmt::Display for HornedOxiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.inner, self.location)
+    }
+}
+
+impl Error for HornedOxiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.inner {
+            HornedOxiErrorKind::InvalidInput(_) => None,
+            HornedOxiErrorKind::HornedError(e) => Some(e),
+            HornedOxiErrorKind::OxigraphError(e) => Some(e),
+            HornedOxiErrorKind::IOError(e) => Some(e),
+            HornedOxiErrorKind::LoaderError(e) => Some(e),
+            HornedOxiErrorKind::IriParseError(e) => Some(e),
+            HornedOxiErrorKind::JsonError(e) => Some(e),
+            HornedOxiErrorKind::XmlError(e) => Some(e),
+            HornedOxiErrorKind::RdfSyntaxError(e) => Some(e),
+            HornedOxiErrorKind::ZipError(e) => Some(e),
+            HornedOxiErrorKind::SparqlSyntaxError(e) => Some(e),
+            HornedOxiErrorKind::QueryEvaluationError(e) => Some(e),
+            HornedOxiErrorKind::UpdateEvaluationError(e) => Some(e),
+        }
+    }
+}
+
 impl From<HornedError> for HornedOxiError {
     #[track_caller]
     fn from(error: HornedError) -> Self {
         HornedOxiError {
             inner: HornedOxiErrorKind::HornedError(error),
-            location: &Location::caller(),
+            location: Location::caller(),
         }
     }
 }
@@ -37,7 +106,7 @@ impl From<StorageError> for HornedOxiError {
     fn from(error: StorageError) -> Self {
         HornedOxiError {
             inner: HornedOxiErrorKind::OxigraphError(error),
-            location: &Location::caller(),
+            location: Location::caller(),
         }
     }
 }
@@ -47,7 +116,7 @@ impl From<LoaderError> for HornedOxiError {
     fn from(error: LoaderError) -> Self {
         HornedOxiError {
             inner: HornedOxiErrorKind::LoaderError(error),
-            location: &Location::caller(),
+            location: Location::caller(),
         }
     }
 }
@@ -57,7 +126,7 @@ impl From<IriParseError> for HornedOxiError {
     fn from(error: IriParseError) -> Self {
         HornedOxiError {
             inner: HornedOxiErrorKind::IriParseError(error),
-            location: &Location::caller(),
+            location: Location::caller(),
         }
     }
 }
@@ -67,7 +136,7 @@ impl From<HornedOxiErrorKind> for HornedOxiError {
     fn from(error: HornedOxiErrorKind) -> Self {
         HornedOxiError {
             inner: error,
-            location: &Location::caller(),
+            location: Location::caller(),
         }
     }
 }
@@ -77,7 +146,87 @@ impl From<std::io::Error> for HornedOxiError {
     fn from(error: std::io::Error) -> Self {
         HornedOxiError {
             inner: HornedOxiErrorKind::IOError(error),
-            location: &Location::caller(),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for HornedOxiError {
+    #[track_caller]
+    fn from(error: serde_json::Error) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::JsonError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<RdfSyntaxError> for HornedOxiError {
+    #[track_caller]
+    fn from(error: RdfSyntaxError) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::RdfSyntaxError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for HornedOxiError {
+    #[track_caller]
+    fn from(error: zip::result::ZipError) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::ZipError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<SparqlSyntaxError> for HornedOxiError {
+    #[track_caller]
+    fn from(error: SparqlSyntaxError) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::SparqlSyntaxError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<QueryEvaluationError> for HornedOxiError {
+    #[track_caller]
+    fn from(error: QueryEvaluationError) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::QueryEvaluationError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<UpdateEvaluationError> for HornedOxiError {
+    #[track_caller]
+    fn from(error: UpdateEvaluationError) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::UpdateEvaluationError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<quick_xml::Error> for HornedOxiError {
+    #[track_caller]
+    fn from(error: quick_xml::Error) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::XmlError(error),
+            location: Location::caller(),
+        }
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for HornedOxiError {
+    #[track_caller]
+    fn from(error: quick_xml::events::attributes::AttrError) -> Self {
+        HornedOxiError {
+            inner: HornedOxiErrorKind::XmlError(error.into()),
+            location: Location::caller(),
         }
     }
 }
@@ -1,9 +1,21 @@
-use crate::horned_oxi::horned_oxi_visitor::{ForVisit, Visit, Walk};
+use crate::horned_oxi::{
+    horned_oxi_visitor::{ForVisit, Visit, Walk},
+    metrics::{MetricsVisitor, OntologyMetrics},
+    vowl::split_iri,
+};
 use horned_owl::{
-    model::{ClassExpression, DeclareClass, EquivalentClasses, ForIRI, IRI, Individual},
+    model::{
+        AnnotationAssertion, AnnotationSubject, AnonymousIndividual, AnnotationValue, ClassExpression, Component, DataProperty, DataRange, DeclareClass, DisjointClasses, DisjointUnion,
+        EquivalentClasses, ForIRI, Individual, InverseObjectProperties, Literal,
+        ObjectPropertyExpression,
+    },
     ontology::set::SetOntology,
+    vocab::{AnnotationBuiltIn, OWL, OWL2Datatype, XSD},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
 };
-use std::{collections::HashMap, fmt, ops::Deref};
 
 #[derive(Clone)]
 pub struct Kind<T>(pub Thing<T>);
@@ -22,11 +34,13 @@ pub enum Node<T> {
     EquivalentClass(Vec<T>),
     Union(T),
     DisjointUnion(T),
+    DisjointClasses(T),
     Intersection(T),
     Complement(T),
     DeprecatedClass(T),
     AnonymousClass(T),
     Literal(T),
+    Datatype(T),
     RdfsClass(T),
     RdfsResource(T),
 }
@@ -48,19 +62,74 @@ impl From<Edge<u32>> for Thing<u32> {
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub enum Edge<T> {
-    Datatype(T, T),
     ObjectProperty(T,T, T),
-    DatatypeProperty(T, T),
+    DatatypeProperty(T, T, T),
     SubclassOf(T, T),
-    InverseProperty(T, T),
+    InverseProperty(T, T, T, T),
     DisjointWith(T, T),
     RdfProperty(T, T),
-    DeprecatedProperty(T, T),
-    ExternalProperty(T, T),
-    ValuesFrom(T, T),
+    DeprecatedProperty(T, T, T),
+    ExternalProperty(T, T, T),
+    ValuesFrom(T, T, T, Restriction),
+    SetOperator(T, T),
     NoDraw,
 }
 
+/// Options controlling how an ontology is drawn.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractConfiguration {
+    /// Draw `DisjointClasses` with more than two members as a single node
+    /// linked to each member, instead of pairwise `DisjointWith` edges.
+    pub group_disjoint: bool,
+    /// Namespaces whose entities are part of the ontology, in addition to
+    /// the ontology IRI itself. Entities outside all of them are external.
+    pub internal_namespaces: Vec<String>,
+    /// Languages to pick labels and comments in, most preferred first.
+    pub languages: Vec<String>,
+    /// Prefix names with the IRIs they stand for, listed as the namespaces
+    /// of the VOWL JSON.
+    pub prefixes: Vec<(String, String)>,
+}
+
+const SKOS_PREF_LABEL: &str = "http://www.w3.org/2004/02/skos/core#prefLabel";
+const SKOS_DEFINITION: &str = "http://www.w3.org/2004/02/skos/core#definition";
+
+/// Language key of annotation values without a language tag.
+pub const UNDEFINED_LANGUAGE: &str = "undefined";
+
+/// Human readable annotations of an entity, each keyed by language tag.
+#[derive(Debug, Clone, Default)]
+pub struct Annotations {
+    pub label: BTreeMap<String, String>,
+    pub comment: BTreeMap<String, String>,
+    pub pref_label: BTreeMap<String, String>,
+    pub definition: BTreeMap<String, String>,
+}
+
+/// The value in the first of `languages` present, then in no language, then
+/// in any language. Within a language, earlier maps take precedence.
+fn preferred<'a>(maps: &[&'a BTreeMap<String, String>], languages: &[String]) -> Option<&'a str> {
+    languages
+        .iter()
+        .map(String::as_str)
+        .chain([UNDEFINED_LANGUAGE])
+        .find_map(|language| maps.iter().find_map(|values| values.get(language)))
+        .or_else(|| maps.iter().find_map(|values| values.values().next()))
+        .map(String::as_str)
+}
+
+/// The kind of property restriction drawn by an `Edge::ValuesFrom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Restriction {
+    SomeValuesFrom,
+    AllValuesFrom,
+    HasValue,
+    HasSelf,
+    MinCardinality(u32),
+    MaxCardinality(u32),
+    ExactCardinality(u32),
+}
+
 impl<T: Clone> ForVisit<Thing<T>> for Kind<T> {
     fn inner(&self) -> Thing<T> {
         self.0.clone()
@@ -73,8 +142,25 @@ pub struct HornedVOWLExtract<A> {
     // [from, edge_type, to]
     edges: Vec<Edge<u32>>,
     iricache: HashMap<A, (u32, Option<u32>)>,
-    domain: HashMap<A, Vec<A>>,
-    range: HashMap<A, Vec<A>>,
+    // Nodes duplicated per usage, mapped to the id of the entity they stand for
+    aliases: HashMap<u32, u32>,
+    next_id: u32,
+    // property -> nodes of its domain and range class expressions
+    domain: HashMap<A, Vec<u32>>,
+    range: HashMap<A, Vec<u32>>,
+    object_properties: BTreeSet<A>,
+    inverses: Vec<(A, A)>,
+    data_domain: HashMap<A, Vec<u32>>,
+    data_range: HashMap<A, Vec<A>>,
+    data_properties: BTreeSet<A>,
+    deprecated: BTreeSet<A>,
+    annotations: HashMap<A, Annotations>,
+    ontology_iri: Option<A>,
+    version_iri: Option<A>,
+    metrics: OntologyMetrics,
+    // Collects `metrics` during the same walk as the extract
+    metrics_visitor: MetricsVisitor<A>,
+    config: ExtractConfiguration,
 }
 
 impl<A> Default for HornedVOWLExtract<A> {
@@ -83,8 +169,22 @@ impl<A> Default for HornedVOWLExtract<A> {
             nodes: vec![],
             edges: vec![],
             iricache: HashMap::new(),
+            aliases: HashMap::new(),
+            next_id: 0,
             domain: HashMap::new(),
             range: HashMap::new(),
+            object_properties: BTreeSet::new(),
+            inverses: vec![],
+            data_domain: HashMap::new(),
+            data_range: HashMap::new(),
+            data_properties: BTreeSet::new(),
+            deprecated: BTreeSet::new(),
+            annotations: HashMap::new(),
+            ontology_iri: None,
+            version_iri: None,
+            metrics: OntologyMetrics::default(),
+            metrics_visitor: MetricsVisitor::default(),
+            config: ExtractConfiguration::default(),
         }
     }
 }
@@ -93,22 +193,502 @@ impl<A: ForIRI> HornedVOWLExtract<A> {
     pub fn insert(&mut self, x: A) -> (bool, u32) {
         let present = self.iricache.contains_key(&x);
         if !present {
-            self.iricache
-                .insert(x.clone(), (self.iricache.len() as u32, None));
+            let id = self.fresh();
+            self.iricache.insert(x.clone(), (id, None));
         }
         (present, self.iricache[&x].0)
     }
+
+    /// Allocate an id that is not bound to any IRI.
+    fn fresh(&mut self) -> u32 {
+        self.next_id += 1;
+        self.next_id - 1
+    }
+
+    /// Allocate a new node id standing for an existing entity, so that the
+    /// entity can be drawn once per usage.
+    fn duplicate(&mut self, x: A) -> u32 {
+        let original = self.insert(x).1;
+        let id = self.fresh();
+        self.aliases.insert(id, original);
+        id
+    }
+
+    fn insert_class(&mut self, x: A) -> u32 {
+        let index = self.insert(x);
+        if !index.0 {
+            self.nodes.push(Node::Class(index.1));
+        }
+        index.1
+    }
+
+    fn insert_thing(&mut self) -> u32 {
+        let index = self.insert(A::from(OWL::Thing.as_ref().to_string()));
+        if !index.0 {
+            self.nodes.push(Node::Thing(index.1));
+        }
+        index.1
+    }
+
+    fn insert_individual(&mut self, i: &Individual<A>) -> u32 {
+        let index = match i {
+            Individual::Named(name) => self.insert(name.0.underlying()),
+            Individual::Anonymous(anonymous) => self.insert(anonymous.0.clone()),
+        };
+        if !index.0 {
+            self.nodes.push(Node::Thing(index.1));
+        }
+        index.1
+    }
+
+    /// Insert a node for one usage of a datatype.
+    fn insert_datatype(&mut self, datatype: A) -> u32 {
+        let literal = datatype.as_ref() == OWL2Datatype::Literal.as_ref();
+        let id = self.duplicate(datatype);
+        self.nodes.push(if literal {
+            Node::Literal(id)
+        } else {
+            Node::Datatype(id)
+        });
+        id
+    }
+
+    fn insert_data_range(&mut self, dr: &DataRange<A>) -> u32 {
+        match dr {
+            DataRange::Datatype(datatype) => self.insert_datatype(datatype.0.underlying()),
+            _ => self.insert_datatype(A::from(OWL2Datatype::Literal.as_ref().to_string())),
+        }
+    }
+
+    /// Insert a class expression, creating nodes for anonymous expressions
+    /// and their operands. Returns the id of the node standing for the
+    /// whole expression.
+    fn insert_class_expression(&mut self, ce: &ClassExpression<A>) -> u32 {
+        let id = match ce {
+            ClassExpression::Class(class) => return self.insert_class(class.0.underlying()),
+            _ => self.fresh(),
+        };
+        if self.insert_restriction(id, ce) {
+            self.nodes.push(Node::AnonymousClass(id));
+            return id;
+        }
+        let operands = match ce {
+            ClassExpression::ObjectUnionOf(v) => {
+                self.nodes.push(Node::Union(id));
+                v.iter().collect()
+            }
+            ClassExpression::ObjectIntersectionOf(v) => {
+                self.nodes.push(Node::Intersection(id));
+                v.iter().collect()
+            }
+            ClassExpression::ObjectComplementOf(inner) => {
+                self.nodes.push(Node::Complement(id));
+                vec![inner.as_ref()]
+            }
+            ClassExpression::ObjectOneOf(individuals) => {
+                self.nodes.push(Node::AnonymousClass(id));
+                for i in individuals {
+                    let to = self.insert_individual(i);
+                    self.edges.push(Edge::SetOperator(id, to));
+                }
+                vec![]
+            }
+            _ => vec![],
+        };
+        for operand in operands {
+            let to = self.insert_class_expression(operand);
+            self.edges.push(Edge::SetOperator(id, to));
+        }
+        id
+    }
+
+    /// Draw a property restriction as an edge starting at `from`, the class
+    /// being restricted. Returns `false` if `ce` is not a restriction.
+    fn insert_restriction(&mut self, from: u32, ce: &ClassExpression<A>) -> bool {
+        use ClassExpression as CE;
+        let (ope, restriction, bce) = match ce {
+            CE::ObjectSomeValuesFrom { ope, bce } => (ope, Restriction::SomeValuesFrom, bce),
+            CE::ObjectAllValuesFrom { ope, bce } => (ope, Restriction::AllValuesFrom, bce),
+            CE::ObjectMinCardinality { n, ope, bce } => (ope, Restriction::MinCardinality(*n), bce),
+            CE::ObjectMaxCardinality { n, ope, bce } => (ope, Restriction::MaxCardinality(*n), bce),
+            CE::ObjectExactCardinality { n, ope, bce } => {
+                (ope, Restriction::ExactCardinality(*n), bce)
+            }
+            CE::ObjectHasValue { ope, i } => {
+                let to = self.insert_individual(i);
+                self.insert_object_restriction(from, ope, Restriction::HasValue, to);
+                return true;
+            }
+            CE::ObjectHasSelf(ope) => {
+                self.insert_object_restriction(from, ope, Restriction::HasSelf, from);
+                return true;
+            }
+            CE::DataSomeValuesFrom { dp, dr } => {
+                self.insert_data_restriction(from, dp, Restriction::SomeValuesFrom, dr);
+                return true;
+            }
+            CE::DataAllValuesFrom { dp, dr } => {
+                self.insert_data_restriction(from, dp, Restriction::AllValuesFrom, dr);
+                return true;
+            }
+            CE::DataMinCardinality { n, dp, dr } => {
+                self.insert_data_restriction(from, dp, Restriction::MinCardinality(*n), dr);
+                return true;
+            }
+            CE::DataMaxCardinality { n, dp, dr } => {
+                self.insert_data_restriction(from, dp, Restriction::MaxCardinality(*n), dr);
+                return true;
+            }
+            CE::DataExactCardinality { n, dp, dr } => {
+                self.insert_data_restriction(from, dp, Restriction::ExactCardinality(*n), dr);
+                return true;
+            }
+            CE::DataHasValue { dp, l } => {
+                let index_dp = self.insert(dp.0.underlying()).1;
+                let datatype = match l {
+                    Literal::Datatype { datatype_iri, .. } => datatype_iri.underlying(),
+                    _ => A::from(OWL2Datatype::Literal.as_ref().to_string()),
+                };
+                let to = self.insert_datatype(datatype);
+                self.edges.push(Edge::ValuesFrom(from, index_dp, to, Restriction::HasValue));
+                return true;
+            }
+            _ => return false,
+        };
+        let to = self.insert_class_expression(bce);
+        self.insert_object_restriction(from, ope, restriction, to);
+        true
+    }
+
+    fn insert_data_restriction(
+        &mut self,
+        from: u32,
+        dp: &DataProperty<A>,
+        restriction: Restriction,
+        dr: &DataRange<A>,
+    ) {
+        let index_dp = self.insert(dp.0.underlying()).1;
+        let to = self.insert_data_range(dr);
+        self.edges.push(Edge::ValuesFrom(from, index_dp, to, restriction));
+    }
+
+    fn insert_object_restriction(
+        &mut self,
+        from: u32,
+        ope: &ObjectPropertyExpression<A>,
+        restriction: Restriction,
+        to: u32,
+    ) {
+        // A restriction on an inverse property points the other way.
+        let (property, from, to) = match ope {
+            ObjectPropertyExpression::ObjectProperty(p) => (p, from, to),
+            ObjectPropertyExpression::InverseObjectProperty(p) => (p, to, from),
+        };
+        let index_ope = self.insert(property.0.underlying()).1;
+        self.edges.push(Edge::ValuesFrom(from, index_ope, to, restriction));
+    }
+
+    /// Resolve everything that depends on the whole ontology having been
+    /// walked, as the components of a `SetOntology` come in no particular
+    /// order.
+    fn finish(&mut self) {
+        self.metrics = std::mem::take(&mut self.metrics_visitor).into_metrics();
+
+        // Domain to range edges of object properties, apart from the
+        // assertion edges between individuals drawn while walking.
+        let mut tbox = vec![];
+        let properties = self
+            .object_properties
+            .iter()
+            .chain(self.domain.keys())
+            .chain(self.range.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        for property in properties {
+            let index_ope = self.insert(property.clone()).1;
+            let domains = match self.domain.get(&property).cloned() {
+                Some(domains) => domains,
+                None => vec![self.insert_thing()],
+            };
+            let ranges = match self.range.get(&property).cloned() {
+                Some(ranges) => ranges,
+                None => vec![self.insert_thing()],
+            };
+            for from in &domains {
+                for to in &ranges {
+                    tbox.push(Edge::ObjectProperty(*from, index_ope, *to));
+                }
+            }
+        }
+
+        // Each inverse pair is drawn as one edge carrying both properties;
+        // an edge of the inverse is implied by the property edge running
+        // the other way.
+        for (property, inverse) in self.inverses.clone() {
+            let index_p = self.insert(property).1;
+            let index_q = self.insert(inverse).1;
+            if index_p == index_q {
+                continue;
+            }
+            let reversed = tbox
+                .iter()
+                .filter_map(|edge| match *edge {
+                    Edge::ObjectProperty(from, p, to) if p == index_p => Some((to, from)),
+                    _ => None,
+                })
+                .collect::<BTreeSet<_>>();
+            let mut paired = BTreeSet::new();
+            tbox.retain(|edge| match *edge {
+                Edge::ObjectProperty(from, q, to)
+                    if q == index_q && reversed.contains(&(from, to)) =>
+                {
+                    paired.insert((to, from));
+                    false
+                }
+                _ => true,
+            });
+            for edge in tbox.iter_mut() {
+                if let Edge::ObjectProperty(from, p, to) = *edge
+                    && p == index_p
+                    && paired.contains(&(from, to))
+                {
+                    *edge = Edge::InverseProperty(from, p, index_q, to);
+                }
+            }
+        }
+        self.edges.extend(tbox);
+
+        let properties = self
+            .data_properties
+            .iter()
+            .chain(self.data_domain.keys())
+            .chain(self.data_range.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let literal = A::from(OWL2Datatype::Literal.as_ref().to_string());
+        for property in properties {
+            let index_dp = self.insert(property.clone()).1;
+            let domains = match self.data_domain.get(&property).cloned() {
+                Some(domains) => domains,
+                None => vec![self.insert_thing()],
+            };
+            let ranges = self
+                .data_range
+                .get(&property)
+                .cloned()
+                .unwrap_or_else(|| vec![literal.clone()]);
+            for from in domains {
+                // Each usage of a datatype gets its own node, as in WebVOWL.
+                for datatype in &ranges {
+                    let to = self.insert_datatype(datatype.clone());
+                    self.edges.push(Edge::DatatypeProperty(from, index_dp, to));
+                }
+            }
+        }
+
+        let external = self
+            .iricache
+            .iter()
+            .filter(|(iri, _)| self.is_external(iri.as_ref()))
+            .map(|(_, (id, _))| *id)
+            .collect::<BTreeSet<_>>();
+        let classes = self
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Class(id) if external.contains(id) => Some(Node::ExternalClass(*id)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        self.nodes.extend(classes);
+        for edge in self.edges.iter_mut() {
+            if let Edge::ObjectProperty(from, p, to) | Edge::DatatypeProperty(from, p, to) = *edge
+                && external.contains(&p)
+            {
+                *edge = Edge::ExternalProperty(from, p, to);
+            }
+        }
+
+        let deprecated = self
+            .deprecated
+            .iter()
+            .filter_map(|iri| self.iricache.get(iri).map(|(id, _)| *id))
+            .collect::<BTreeSet<_>>();
+        let classes = self
+            .nodes
+            .iter()
+            .filter_map(|node| match node {
+                Node::Class(id) if deprecated.contains(id) => Some(Node::DeprecatedClass(*id)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        self.nodes.extend(classes);
+        for edge in self.edges.iter_mut() {
+            if let Edge::ObjectProperty(from, p, to) | Edge::DatatypeProperty(from, p, to) = *edge
+                && deprecated.contains(&p)
+            {
+                *edge = Edge::DeprecatedProperty(from, p, to);
+            }
+        }
+    }
+
+    /// Whether `iri` lies outside the ontology IRI and the configured
+    /// internal namespaces. Nothing is external when neither is known.
+    pub fn is_external(&self, iri: &str) -> bool {
+        let mut namespaces = self
+            .ontology_iri
+            .iter()
+            .map(|iri| iri.as_ref())
+            .chain(self.config.internal_namespaces.iter().map(String::as_str))
+            .peekable();
+        if namespaces.peek().is_none() {
+            return false;
+        }
+        !namespaces.any(|ns| {
+            iri.strip_prefix(ns).is_some_and(|rest| {
+                rest.is_empty()
+                    || ns.ends_with(['#', '/'])
+                    || rest.starts_with(['#', '/'])
+            })
+        })
+    }
+
+    pub fn annotations(&self, iri: &str) -> Option<&Annotations> {
+        self.annotations.get(iri)
+    }
+
+    /// The label of `iri` in the preferred languages, preferring
+    /// `skos:prefLabel` over `rdfs:label`, or its local name if it has none.
+    pub fn label(&self, iri: &str) -> String {
+        let languages = &self.config.languages;
+        self.annotations(iri)
+            .and_then(|a| preferred(&[&a.pref_label, &a.label], languages))
+            .map(str::to_string)
+            .unwrap_or_else(|| match split_iri(iri).1 {
+                "" => iri.to_string(),
+                local => local.to_string(),
+            })
+    }
+
+    /// The comment of `iri` in the preferred languages, preferring
+    /// `rdfs:comment` over `skos:definition`.
+    pub fn comment(&self, iri: &str) -> Option<&str> {
+        let languages = &self.config.languages;
+        self.annotations(iri)
+            .and_then(|a| preferred(&[&a.comment, &a.definition], languages))
+    }
+
+    pub fn nodes(&self) -> &[Node<u32>] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge<u32>] {
+        &self.edges
+    }
+
+    pub fn iricache(&self) -> &HashMap<A, (u32, Option<u32>)> {
+        &self.iricache
+    }
+
+    pub fn aliases(&self) -> &HashMap<u32, u32> {
+        &self.aliases
+    }
+
+    /// The first id not yet handed out to a node or property.
+    pub fn next_id(&self) -> u32 {
+        self.next_id
+    }
+
+    pub fn ontology_iri(&self) -> Option<&A> {
+        self.ontology_iri.as_ref()
+    }
+
+    pub fn version_iri(&self) -> Option<&A> {
+        self.version_iri.as_ref()
+    }
+
+    pub fn prefixes(&self) -> &[(String, String)] {
+        &self.config.prefixes
+    }
+
+    pub fn metrics(&self) -> &OntologyMetrics {
+        &self.metrics
+    }
+}
+
+impl<A: ForIRI> HornedVOWLExtract<A> {
+    pub fn with_config(ontology: &SetOntology<A>, config: ExtractConfiguration) -> Self {
+        let mut walk = Walk::<A, u32, Self>::new(Self {
+            config,
+            ..Self::default()
+        });
+        walk.set_ontology(None, ontology);
+        let mut extract = walk.into_visit();
+        extract.finish();
+        extract
+    }
+}
+
+impl<A: ForIRI> HornedVOWLExtract<A> {
+    /// Extract from loose components rather than an ontology, as read from
+    /// a store by [`crate::horned_oxi::sparql`]. `rdfs_classes` are drawn as
+    /// `rdfs:Class` rather than `owl:Class`.
+    pub fn from_components<'c>(
+        components: impl IntoIterator<Item = &'c Component<A>>,
+        rdfs_classes: impl IntoIterator<Item = A>,
+        config: ExtractConfiguration,
+    ) -> Self
+    where
+        A: 'c,
+    {
+        let mut walk = Walk::<A, u32, Self>::new(Self {
+            config,
+            ..Self::default()
+        });
+        for component in components {
+            walk.component(None, component);
+        }
+        let mut extract = walk.into_visit();
+        for class in rdfs_classes {
+            let id = extract.insert_class(class);
+            extract.nodes.push(Node::RdfsClass(id));
+        }
+        extract.finish();
+        extract
+    }
 }
 
 impl<A: ForIRI> From<SetOntology<A>> for HornedVOWLExtract<A> {
     fn from(ontology: SetOntology<A>) -> Self {
-        let mut walk = Walk::<A, u32, Self>::new(Self::default());
-        walk.set_ontology(None, &ontology);
-        walk.into_visit()
+        Self::with_config(&ontology, ExtractConfiguration::default())
     }
 }
 
 impl<A: ForIRI> Visit<A, u32> for HornedVOWLExtract<A> {
+    fn visit_component(&mut self, parent: Option<Kind<u32>>, cmp: &Component<A>) -> Option<Kind<u32>> {
+        self.metrics_visitor.visit_component(parent, cmp)
+    }
+    fn visit_class_expression(&mut self, parent: Option<Kind<u32>>, cmp: &ClassExpression<A>) -> Option<Kind<u32>> {
+        self.metrics_visitor.visit_class_expression(parent, cmp)
+    }
+    fn visit_object_property_expression(&mut self, parent: Option<Kind<u32>>, cmp: &ObjectPropertyExpression<A>) -> Option<Kind<u32>> {
+        self.metrics_visitor.visit_object_property_expression(parent, cmp)
+    }
+    fn visit_data_range(&mut self, parent: Option<Kind<u32>>, cmp: &DataRange<A>) -> Option<Kind<u32>> {
+        self.metrics_visitor.visit_data_range(parent, cmp)
+    }
+    fn visit_anonymous_individual(&mut self, parent: Option<Kind<u32>>, cmp: &AnonymousIndividual<A>) -> Option<Kind<u32>> {
+        self.metrics_visitor.visit_anonymous_individual(parent, cmp)
+    }
+    fn visit_ontology_id(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::OntologyID<A>,
+    ) -> Option<Kind<u32>> {
+        self.ontology_iri = cmp.iri.as_ref().map(|iri| iri.underlying());
+        self.version_iri = cmp.viri.as_ref().map(|iri| iri.underlying());
+        None
+    }
     fn visit_declare_class(
         &mut self,
         _: Option<Kind<u32>>,
@@ -120,25 +700,101 @@ impl<A: ForIRI> Visit<A, u32> for HornedVOWLExtract<A> {
         }
         Some(Kind(Thing::Node(Node::Class(index.1))))
     }
+    fn visit_declare_object_property(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::DeclareObjectProperty<A>,
+    ) -> Option<Kind<u32>> {
+        let property = cmp.0.underlying();
+        self.insert(property.clone());
+        self.object_properties.insert(property);
+        None
+    }
+    fn visit_object_property_domain(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::ObjectPropertyDomain<A>,
+    ) -> Option<Kind<u32>> {
+        let class = self.insert_class_expression(&cmp.ce);
+        // The domain of an inverse property is the range of the property.
+        let (property, map) = match &cmp.ope {
+            ObjectPropertyExpression::ObjectProperty(p) => (p, &mut self.domain),
+            ObjectPropertyExpression::InverseObjectProperty(p) => (p, &mut self.range),
+        };
+        map.entry(property.0.underlying()).or_default().push(class);
+        None
+    }
+    fn visit_object_property_range(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::ObjectPropertyRange<A>,
+    ) -> Option<Kind<u32>> {
+        let class = self.insert_class_expression(&cmp.ce);
+        let (property, map) = match &cmp.ope {
+            ObjectPropertyExpression::ObjectProperty(p) => (p, &mut self.range),
+            ObjectPropertyExpression::InverseObjectProperty(p) => (p, &mut self.domain),
+        };
+        map.entry(property.0.underlying()).or_default().push(class);
+        None
+    }
+    fn visit_declare_data_property(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::DeclareDataProperty<A>,
+    ) -> Option<Kind<u32>> {
+        let property = cmp.0.underlying();
+        self.insert(property.clone());
+        self.data_properties.insert(property);
+        None
+    }
+    fn visit_data_property_domain(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::DataPropertyDomain<A>,
+    ) -> Option<Kind<u32>> {
+        let class = self.insert_class_expression(&cmp.ce);
+        self.data_domain
+            .entry(cmp.dp.0.underlying())
+            .or_default()
+            .push(class);
+        None
+    }
+    fn visit_data_property_range(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &horned_owl::model::DataPropertyRange<A>,
+    ) -> Option<Kind<u32>> {
+        // Only named datatypes are drawn, anything else is a literal.
+        let datatype = match &cmp.dr {
+            DataRange::Datatype(datatype) => datatype.0.underlying(),
+            _ => A::from(OWL2Datatype::Literal.as_ref().to_string()),
+        };
+        self.data_range
+            .entry(cmp.dp.0.underlying())
+            .or_default()
+            .push(datatype);
+        None
+    }
     fn visit_equivalent_classes(
         &mut self,
         _: Option<Kind<u32>>,
         cmp: &EquivalentClasses<A>,
     ) -> Option<Kind<u32>> {
-        let mut equivalent_classes = vec![];
-        //let index = self.get_insert(cmp.0.underlying());
-        for class in &cmp.0 {
-            match class {
-                ClassExpression::Class(name) => {
-                    let index = self.insert(name.0.underlying());
-                    if !index.0 {
-                        equivalent_classes.push(index.1);
-                    }
-                }
-                _ => {}
+        // Named classes come first, so the equivalence is drawn under one of
+        // them rather than under an anonymous expression.
+        let mut classes = cmp.0.iter().collect::<Vec<_>>();
+        classes.sort_by_key(|ce| !matches!(ce, ClassExpression::Class(_)));
+        let mut equivalent_classes: Vec<u32> = Vec::new();
+        for ce in classes {
+            // Restrictions are drawn from the class they define.
+            if let Some(&first) = equivalent_classes.first()
+                && self.insert_restriction(first, ce)
+            {
+                continue;
             }
+            equivalent_classes.push(self.insert_class_expression(ce));
         }
-        if !equivalent_classes.is_empty() {
+        if equivalent_classes.len() > 1 {
             self.nodes.push(Node::EquivalentClass(equivalent_classes));
             self.nodes
                 .last()
@@ -148,6 +804,112 @@ impl<A: ForIRI> Visit<A, u32> for HornedVOWLExtract<A> {
             None
         }
     }
+    fn visit_disjoint_classes(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &DisjointClasses<A>,
+    ) -> Option<Kind<u32>> {
+        let classes = cmp
+            .0
+            .iter()
+            .map(|ce| self.insert_class_expression(ce))
+            .collect::<Vec<_>>();
+        if self.config.group_disjoint && classes.len() > 2 {
+            let id = self.fresh();
+            self.nodes.push(Node::DisjointClasses(id));
+            for to in classes {
+                self.edges.push(Edge::DisjointWith(id, to));
+            }
+            return self.nodes.last().cloned().map(|node| Kind(node.into()));
+        }
+        for (i, &from) in classes.iter().enumerate() {
+            for &to in &classes[i + 1..] {
+                self.edges.push(Edge::DisjointWith(from, to));
+            }
+        }
+        self.edges.last().cloned().map(|edge| Kind(edge.into()))
+    }
+    fn visit_disjoint_union(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &DisjointUnion<A>,
+    ) -> Option<Kind<u32>> {
+        let class = self.insert_class(cmp.0.0.underlying());
+        let id = self.fresh();
+        self.nodes.push(Node::DisjointUnion(id));
+        for ce in &cmp.1 {
+            let to = self.insert_class_expression(ce);
+            self.edges.push(Edge::SetOperator(id, to));
+        }
+        // The defined class is equivalent to the union of its operands.
+        self.nodes.push(Node::EquivalentClass(vec![class, id]));
+        self.nodes.last().cloned().map(|node| Kind(node.into()))
+    }
+    fn visit_annotation_assertion(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &AnnotationAssertion<A>,
+    ) -> Option<Kind<u32>> {
+        // Deprecation is applied in `finish`, once every entity is known.
+        if let (
+            AnnotationSubject::IRI(iri),
+            AnnotationValue::Literal(Literal::Datatype {
+                literal,
+                datatype_iri,
+            }),
+        ) = (&cmp.subject, &cmp.ann.av)
+            && cmp.ann.ap.0.as_ref() == AnnotationBuiltIn::Deprecated.as_ref()
+            && datatype_iri.as_ref() == XSD::Boolean.as_ref()
+            && (literal == "true" || literal == "1")
+        {
+            self.deprecated.insert(iri.underlying());
+        }
+        let (AnnotationSubject::IRI(iri), AnnotationValue::Literal(literal)) =
+            (&cmp.subject, &cmp.ann.av)
+        else {
+            return None;
+        };
+        let (value, language) = match literal {
+            Literal::Simple { literal } | Literal::Datatype { literal, .. } => {
+                (literal, UNDEFINED_LANGUAGE)
+            }
+            Literal::Language { literal, lang } => (literal, lang.as_str()),
+        };
+        type Field = fn(&mut Annotations) -> &mut BTreeMap<String, String>;
+        let fields: [(&str, Field); 4] = [
+            (AnnotationBuiltIn::Label.as_ref(), |a| &mut a.label),
+            (AnnotationBuiltIn::Comment.as_ref(), |a| &mut a.comment),
+            (SKOS_PREF_LABEL, |a| &mut a.pref_label),
+            (SKOS_DEFINITION, |a| &mut a.definition),
+        ];
+        let (_, field) = fields
+            .into_iter()
+            .find(|(property, _)| *property == cmp.ann.ap.0.as_ref())?;
+        let values = field(self.annotations.entry(iri.underlying()).or_default());
+        // Keep the smallest of several values, so the result does not
+        // depend on the order the axioms are visited in.
+        values
+            .entry(language.to_string())
+            .and_modify(|v| {
+                if value < v {
+                    v.clone_from(value)
+                }
+            })
+            .or_insert_with(|| value.clone());
+        None
+    }
+    fn visit_inverse_object_properties(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &InverseObjectProperties<A>,
+    ) -> Option<Kind<u32>> {
+        let property = cmp.0.0.underlying();
+        let inverse = cmp.1.0.underlying();
+        self.object_properties.insert(property.clone());
+        self.object_properties.insert(inverse.clone());
+        self.inverses.push((property, inverse));
+        None
+    }
     fn visit_named_individual(
             &mut self,
             _: Option<Kind<u32>>,
@@ -164,123 +926,670 @@ impl<A: ForIRI> Visit<A, u32> for HornedVOWLExtract<A> {
         _: Option<Kind<u32>>,
         cmp: &horned_owl::model::ObjectPropertyAssertion<A>,
     ) -> Option<Kind<u32>> {
-        let index_ope = self.insert(cmp.ope.as_property().unwrap().0.underlying());
-        
-        let index_from = match cmp.from.clone() {
-            Individual::Named(name) => {
-                self.insert(name.0.underlying())
-            }
-            Individual::Anonymous(anonymous) => {
-                self.insert(anonymous.0)
-            }
+        let index_from = self.insert_individual(&cmp.from);
+        let index_to = self.insert_individual(&cmp.to);
+        // An assertion on an inverse property points the other way.
+        let (property, index_from, index_to) = match &cmp.ope {
+            ObjectPropertyExpression::ObjectProperty(p) => (p, index_from, index_to),
+            ObjectPropertyExpression::InverseObjectProperty(p) => (p, index_to, index_from),
         };
-        let index_to = match cmp.to.clone() {
-            Individual::Named(name) => {
-                self.insert(name.0.underlying())
-            }
-            Individual::Anonymous(anonymous) => {
-                self.insert(anonymous.0)
-            }
-        };
-        self.edges.push(Edge::ObjectProperty(index_from.1, index_ope.1, index_to.1));
-        Some(Kind(Thing::Edge(Edge::ObjectProperty(index_from.1, index_ope.1, index_to.1))))
-    }
-    fn visit_sub_class_of(&mut self, _: Option<Kind<u32>>, cmp: &horned_owl::model::SubClassOf<A>) -> Option<Kind<u32>> {
-        
-        let index_sub = match &cmp.sub {
-            ClassExpression::Class(name) => {
-                Some(self.insert(name.0.underlying()))
-            }
-            _ => None
-        };
-        let index_sup = match &cmp.sup {
-            ClassExpression::Class(name) => {
-                Some(self.insert(name.0.underlying()))
-            }
-            _ => None
-        };
-        if !index_sub.is_some_and(|x| x.0) {
-            self.nodes.push(Node::Class(index_sub.unwrap().1));
-        }
-        if !index_sup.is_some_and(|x| x.0) {
-            self.nodes.push(Node::Class(index_sup.unwrap().1));
+        let index_ope = self.insert(property.0.underlying());
+        self.edges.push(Edge::ObjectProperty(index_from, index_ope.1, index_to));
+        Some(Kind(Thing::Edge(Edge::ObjectProperty(index_from, index_ope.1, index_to))))
+    }
+    fn visit_sub_class_of(&mut self, parent: Option<Kind<u32>>, cmp: &horned_owl::model::SubClassOf<A>) -> Option<Kind<u32>> {
+        self.metrics_visitor.visit_sub_class_of(parent, cmp);
+        let index_sub = self.insert_class_expression(&cmp.sub);
+        // A restriction on the superclass is drawn from the subclass itself.
+        if self.insert_restriction(index_sub, &cmp.sup) {
+            return self.edges.last().cloned().map(|edge| Kind(Thing::Edge(edge)));
         }
-        
-        self.edges.push(Edge::SubclassOf(index_sub.unwrap().1, index_sup.unwrap().1));
-        Some(Kind(Thing::Edge(Edge::SubclassOf(index_sub.unwrap().1, index_sup.unwrap().1))))
-        
+        let index_sup = self.insert_class_expression(&cmp.sup);
+        self.edges.push(Edge::SubclassOf(index_sub, index_sup));
+        Some(Kind(Thing::Edge(Edge::SubclassOf(index_sub, index_sup))))
+    }
+}
+
+impl<A: ForIRI> fmt::Display for HornedVOWLExtract<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "HornedVOWLExtract {{ nodes: {:#?}\nedges: {:#?}\nindex: {:#?} }}",
+            self.nodes, self.edges, self.iricache
+        )
     }
 }
 
-/*
-impl<A: ForIRI> From<ComponentMappedOntology<A, Rc<AnnotatedComponent<A>>>> for HornedConvert<A> {
-    fn from(ontology: ComponentMappedOntology<A, Rc<AnnotatedComponent<A>>>) -> Self {
-        let mut i: u32 = 0;
-        let mut index = HashMap::<A, u32>::new();
-        let mut nodes = Vec::new();
-        let mut edges = Vec::new();
+#[cfg(test)]
+mod test {
+    use super::*;
+    use horned_owl::{
+        io::{ParserConfiguration, ofn},
+        model::RcStr,
+    };
+    use std::{fs::File, io::BufReader};
 
-        nodes.append(ontology
-            .declare_class()
-            .into_iter()
-            .map(|cmp| {
-                let ul = cmp.0.underlying();
-                if !index.contains_key(&ul) {
-                    index.insert(ul.clone(), i.clone());
-                    i += 1;
-                }
-                NodeType::Class(index[&ul.clone()])
+    fn extract(resource: &str) -> HornedVOWLExtract<RcStr> {
+        let mut reader = BufReader::new(File::open(resource).unwrap());
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut reader, ParserConfiguration::default()).unwrap();
+        ontology.into()
+    }
+
+    fn id(extract: &HornedVOWLExtract<RcStr>, iri: &str) -> u32 {
+        extract.iricache()[iri].0
+    }
+
+    fn object_properties(extract: &HornedVOWLExtract<RcStr>) -> Vec<(u32, u32, u32)> {
+        extract
+            .edges()
+            .iter()
+            .filter_map(|edge| match edge {
+                Edge::ObjectProperty(from, prop, to) => Some((*from, *prop, *to)),
+                _ => None,
             })
-            .collect::<Vec<NodeType>>().as_mut()
+            .collect()
+    }
+
+    fn datatype_properties(extract: &HornedVOWLExtract<RcStr>) -> Vec<(u32, u32, u32)> {
+        extract
+            .edges()
+            .iter()
+            .filter_map(|edge| match edge {
+                Edge::DatatypeProperty(from, prop, to) => Some((*from, *prop, *to)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_object_property_domain() {
+        let extract = extract("data/owl-functional/object-property-domain.ofn");
+        let thing = id(&extract, OWL::Thing.as_ref());
+        assert_eq!(
+            object_properties(&extract),
+            vec![(
+                id(&extract, "http://www.example.com/iri#C"),
+                id(&extract, "http://www.example.com/iri#r"),
+                thing
+            )]
+        );
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Thing(t) if *t == thing))
         );
+    }
 
-        edges.append(ontology
-            .object_property_assertion()
-            .into_iter()
-            .map(|cmp| {
-                let ul = cmp.ope.as_property().unwrap().0.underlying();
-                EdgeType::ObjectProperty(index[&cmp.to.clone()], index[&cmp.from.clone()])
+    #[test]
+    fn test_object_property_range() {
+        let extract = extract("data/owl-functional/object-property-range.ofn");
+        assert_eq!(
+            object_properties(&extract),
+            vec![(
+                id(&extract, OWL::Thing.as_ref()),
+                id(&extract, "http://www.example.com/iri#r"),
+                id(&extract, "http://www.example.com/iri#C")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_property_anonymous_domain_and_range() {
+        let ofn = "Prefix(o:=<http://www.example.com/iri#>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(o:A))
+Declaration(Class(o:B))
+Declaration(ObjectProperty(o:r))
+Declaration(DataProperty(o:d))
+ObjectPropertyDomain(o:r ObjectUnionOf(o:A o:B))
+ObjectPropertyRange(o:r ObjectIntersectionOf(o:A o:B))
+DataPropertyDomain(o:d ObjectUnionOf(o:A o:B))
+)";
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut ofn.as_bytes(), ParserConfiguration::default()).unwrap();
+        let extract: HornedVOWLExtract<RcStr> = ontology.into();
+        let r = id(&extract, "http://www.example.com/iri#r");
+        let edges = object_properties(&extract);
+        assert_eq!(edges.len(), 1);
+        let (from, prop, to) = edges[0];
+        assert_eq!(prop, r);
+        assert!(extract.nodes().iter().any(|n| matches!(n, Node::Union(u) if *u == from)));
+        assert!(extract.nodes().iter().any(|n| matches!(n, Node::Intersection(i) if *i == to)));
+
+        let edges = datatype_properties(&extract);
+        assert_eq!(edges.len(), 1);
+        let from = edges[0].0;
+        assert!(extract.nodes().iter().any(|n| matches!(n, Node::Union(u) if *u == from)));
+    }
+
+    #[test]
+    fn test_object_property_declaration() {
+        let extract = extract("data/owl-functional/oproperty.ofn");
+        let thing = id(&extract, OWL::Thing.as_ref());
+        assert_eq!(
+            object_properties(&extract),
+            vec![(thing, id(&extract, "http://www.example.com/iri#z"), thing)]
+        );
+    }
+
+    #[test]
+    fn test_data_property_domain() {
+        let extract = extract("data/owl-functional/data-property-domain.ofn");
+        let edges = datatype_properties(&extract);
+        assert_eq!(edges.len(), 1);
+        let (from, prop, to) = edges[0];
+        assert_eq!(from, id(&extract, "http://www.example.com/iri#C"));
+        assert_eq!(prop, id(&extract, "http://www.example.com/iri#dp"));
+        assert_eq!(
+            extract.aliases()[&to],
+            id(&extract, OWL2Datatype::Literal.as_ref())
+        );
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Literal(l) if *l == to))
+        );
+    }
+
+    #[test]
+    fn test_data_property_range() {
+        let extract = extract("data/owl-functional/data-property-range.ofn");
+        let edges = datatype_properties(&extract);
+        assert_eq!(edges.len(), 1);
+        let (from, _, to) = edges[0];
+        assert_eq!(from, id(&extract, OWL::Thing.as_ref()));
+        assert_eq!(
+            extract.aliases()[&to],
+            id(&extract, "http://www.w3.org/2001/XMLSchema#real")
+        );
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Datatype(d) if *d == to))
+        );
+    }
+
+    #[test]
+    fn test_data_property_duplicated_datatype() {
+        let extract = extract("data/owl-functional/data-property-equivalent.ofn");
+        let edges = datatype_properties(&extract);
+        assert_eq!(edges.len(), 2);
+        assert_ne!(edges[0].2, edges[1].2, "Each usage needs its own node");
+        assert_eq!(extract.aliases()[&edges[0].2], extract.aliases()[&edges[1].2]);
+    }
+
+    fn operands(extract: &HornedVOWLExtract<RcStr>, from: u32) -> Vec<u32> {
+        extract
+            .edges()
+            .iter()
+            .filter_map(|edge| match edge {
+                Edge::SetOperator(f, to) if *f == from => Some(*to),
+                _ => None,
             })
-            .collect::<Vec<EdgeType>>().as_mut());
+            .collect()
+    }
 
-        nodes.append(ontology
-            .equivalent_class()
-            .into_iter()
-            .map(|cmp| {
-                let ul = cmp.0;
-                for class in ul {
-                    match class {
-                        ClassExpression::Class(name) => {
-                            if !index.contains_key(&name) {
-                                index.insert(name.0.underlying().clone(), i.clone());
-                                i += 1;
-                            }
-                        }
-                    }
-                    if !index.contains_key(&class) {
-                        index.insert(class.clone(), i.clone());
-                        i += 1;
-                    }
-                }
-                NodeType::EquivalentClass(ul.iter().map(|class| index[&class.clone()]).collect())
-            }).collect::<Vec<NodeType>>().as_mut());
-        //let nodes = ontology.index().iter().map(|cmp| cmp.kind()).collect();
-        Self {
-            nodes,
-            edges: vec![],
-            index,
-            phantom: PhantomData
+    fn superclass(extract: &HornedVOWLExtract<RcStr>, sub: u32) -> u32 {
+        extract
+            .edges()
+            .iter()
+            .find_map(|edge| match edge {
+                Edge::SubclassOf(s, sup) if *s == sub => Some(*sup),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_union() {
+        let extract = extract("data/owl-functional/or.ofn");
+        let union = superclass(&extract, id(&extract, "http://www.example.com/iri#A"));
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Union(u) if *u == union))
+        );
+        let mut operands = operands(&extract, union);
+        operands.sort();
+        let mut expected = ["B", "C", "D"]
+            .map(|c| id(&extract, &format!("http://www.example.com/iri#{}", c)))
+            .to_vec();
+        expected.sort();
+        assert_eq!(operands, expected);
+    }
+
+    #[test]
+    fn test_complement() {
+        let extract = extract("data/owl-functional/not.ofn");
+        let complement = superclass(&extract, id(&extract, "http://www.example.com/iri#B"));
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Complement(c) if *c == complement))
+        );
+        assert_eq!(
+            operands(&extract, complement),
+            vec![id(&extract, "http://www.example.com/iri#A")]
+        );
+    }
+
+    #[test]
+    fn test_nested_intersection() {
+        let extract = extract("data/owl-functional/and-complex.ofn");
+        let intersection = superclass(&extract, id(&extract, "http://www.example.com/iri#A"));
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Intersection(i) if *i == intersection))
+        );
+        assert!(
+            operands(&extract, intersection).contains(&id(&extract, "http://www.example.com/iri#D"))
+        );
+    }
+
+    fn restrictions(extract: &HornedVOWLExtract<RcStr>) -> Vec<(u32, u32, u32, Restriction)> {
+        extract
+            .edges()
+            .iter()
+            .filter_map(|edge| match edge {
+                Edge::ValuesFrom(from, p, to, r) => Some((*from, *p, *to, *r)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_some_values_from() {
+        let extract = extract("data/owl-functional/some.ofn");
+        let b = id(&extract, "http://www.example.com/iri#B");
+        assert_eq!(
+            restrictions(&extract),
+            vec![(
+                b,
+                id(&extract, "http://www.example.com/iri#r"),
+                id(&extract, "http://www.example.com/iri#A"),
+                Restriction::SomeValuesFrom
+            )]
+        );
+        assert!(
+            !extract
+                .edges()
+                .iter()
+                .any(|edge| matches!(edge, Edge::SubclassOf(s, _) if *s == b))
+        );
+    }
+
+    #[test]
+    fn test_some_values_from_inverse() {
+        let extract = extract("data/owl-functional/some-inverse.ofn");
+        assert_eq!(
+            restrictions(&extract),
+            vec![(
+                id(&extract, "http://www.example.com/iri#A"),
+                id(&extract, "http://www.example.com/iri#r"),
+                id(&extract, "http://www.example.com/iri#B"),
+                Restriction::SomeValuesFrom
+            )]
+        );
+    }
+
+    #[test]
+    fn test_cardinality() {
+        for (resource, restriction) in [
+            ("object-min-cardinality", Restriction::MinCardinality(1)),
+            ("object-max-cardinality", Restriction::MaxCardinality(1)),
+            ("object-exact-cardinality", Restriction::ExactCardinality(1)),
+        ] {
+            let extract = extract(&format!("data/owl-functional/{}.ofn", resource));
+            let restrictions = restrictions(&extract);
+            assert_eq!(restrictions.len(), 1, "{}", resource);
+            assert_eq!(restrictions[0].0, id(&extract, "http://www.example.com/iri#C"));
+            assert_eq!(restrictions[0].3, restriction, "{}", resource);
         }
     }
-}
-*/
 
-impl<A: ForIRI> fmt::Display for HornedVOWLExtract<A> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "HornedConvert {{ nodes: {:#?}\nedges: {:#?}\nindex: {:#?} }}",
-            self.nodes, self.edges, self.iricache
+    #[test]
+    fn test_has_value() {
+        let extract = extract("data/owl-functional/object-has-value.ofn");
+        let (_, _, to, restriction) = restrictions(&extract)[0];
+        assert_eq!(to, id(&extract, "http://www.example.com/iri#I"));
+        assert_eq!(restriction, Restriction::HasValue);
+    }
+
+    #[test]
+    fn test_data_some_values_from() {
+        let extract = extract("data/owl-functional/data-some.ofn");
+        let (from, _, to, _) = restrictions(&extract)[0];
+        assert_eq!(from, id(&extract, "http://www.example.com/iri#C"));
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::Datatype(d) if *d == to))
+        );
+    }
+
+    fn disjoint_with(extract: &HornedVOWLExtract<RcStr>) -> Vec<(u32, u32)> {
+        extract
+            .edges()
+            .iter()
+            .filter_map(|edge| match edge {
+                Edge::DisjointWith(from, to) => Some((*from, *to)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const DISJOINT_THREE: &str = "Prefix(o:=<http://www.example.com/iri#>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(o:A))
+Declaration(Class(o:B))
+Declaration(Class(o:C))
+DisjointClasses(o:A o:B o:C)
+)";
+
+    #[test]
+    fn test_disjoint_classes() {
+        let extract = extract("data/owl-functional/disjoint-class.ofn");
+        let a = id(&extract, "http://www.example.com/iri#A");
+        let b = id(&extract, "http://www.example.com/iri#B");
+        assert_eq!(disjoint_with(&extract), vec![(a, b)]);
+    }
+
+    #[test]
+    fn test_disjoint_classes_pairwise() {
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut DISJOINT_THREE.as_bytes(), ParserConfiguration::default())
+                .unwrap();
+        let extract: HornedVOWLExtract<RcStr> = ontology.into();
+        assert_eq!(disjoint_with(&extract).len(), 3);
+    }
+
+    #[test]
+    fn test_disjoint_classes_grouped() {
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut DISJOINT_THREE.as_bytes(), ParserConfiguration::default())
+                .unwrap();
+        let extract = HornedVOWLExtract::with_config(
+            &ontology,
+            ExtractConfiguration {
+                group_disjoint: true,
+                ..Default::default()
+            },
+        );
+        let edges = disjoint_with(&extract);
+        assert_eq!(edges.len(), 3);
+        let group = edges[0].0;
+        assert!(edges.iter().all(|(from, _)| *from == group));
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::DisjointClasses(d) if *d == group))
+        );
+    }
+
+    #[test]
+    fn test_disjoint_union() {
+        let extract = extract("data/owl-functional/disjoint-union.ofn");
+        let a = id(&extract, "http://www.example.com/iri#A");
+        let union = extract
+            .nodes()
+            .iter()
+            .find_map(|node| match node {
+                Node::DisjointUnion(u) => Some(*u),
+                _ => None,
+            })
+            .unwrap();
+        let mut operands = operands(&extract, union);
+        operands.sort();
+        let mut expected = ["B", "C"]
+            .map(|c| id(&extract, &format!("http://www.example.com/iri#{}", c)))
+            .to_vec();
+        expected.sort();
+        assert_eq!(operands, expected);
+        assert!(
+            extract
+                .nodes()
+                .iter()
+                .any(|node| matches!(node, Node::EquivalentClass(e) if *e == vec![a, union]))
+        );
+    }
+
+    #[test]
+    fn test_deprecated() {
+        let ofn = "Prefix(o:=<http://www.example.com/iri#>)
+Prefix(owl:=<http://www.w3.org/2002/07/owl#>)
+Prefix(xsd:=<http://www.w3.org/2001/XMLSchema#>)
+Ontology(<http://www.example.com/iri>
+AnnotationAssertion(owl:deprecated o:A \"true\"^^xsd:boolean)
+AnnotationAssertion(owl:deprecated o:r \"true\"^^xsd:boolean)
+AnnotationAssertion(owl:deprecated o:B \"false\"^^xsd:boolean)
+Declaration(Class(o:A))
+Declaration(Class(o:B))
+Declaration(ObjectProperty(o:r))
+ObjectPropertyDomain(o:r o:A)
+ObjectPropertyRange(o:r o:B)
+)";
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut ofn.as_bytes(), ParserConfiguration::default()).unwrap();
+        let extract: HornedVOWLExtract<RcStr> = ontology.into();
+        let a = id(&extract, "http://www.example.com/iri#A");
+        let b = id(&extract, "http://www.example.com/iri#B");
+        let r = id(&extract, "http://www.example.com/iri#r");
+        let deprecated = extract
+            .nodes()
+            .iter()
+            .filter_map(|node| match node {
+                Node::DeprecatedClass(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(deprecated, vec![a]);
+        assert!(
+            extract
+                .edges()
+                .iter()
+                .any(|edge| matches!(edge, Edge::DeprecatedProperty(f, p, t) if (*f, *p, *t) == (a, r, b)))
+        );
+        assert!(object_properties(&extract).is_empty());
+        let vowl = extract.to_vowl();
+        assert!(vowl.class.iter().any(|c| c.kind == "owl:DeprecatedClass"));
+        assert!(vowl.property.iter().any(|p| p.kind == "owl:DeprecatedProperty"));
+    }
+
+    const EXTERNAL: &str = "Prefix(o:=<http://www.example.com/iri#>)
+Prefix(foaf:=<http://xmlns.com/foaf/0.1/>)
+Prefix(dc:=<http://purl.org/dc/elements/1.1/>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(o:A))
+Declaration(Class(foaf:Person))
+Declaration(ObjectProperty(foaf:knows))
+Declaration(DataProperty(dc:title))
+ObjectPropertyDomain(foaf:knows o:A)
+ObjectPropertyRange(foaf:knows foaf:Person)
+DataPropertyDomain(dc:title o:A)
+)";
+
+    fn external(config: ExtractConfiguration) -> HornedVOWLExtract<RcStr> {
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut EXTERNAL.as_bytes(), ParserConfiguration::default()).unwrap();
+        HornedVOWLExtract::with_config(&ontology, config)
+    }
+
+    #[test]
+    fn test_external() {
+        let extract = external(ExtractConfiguration::default());
+        let person = id(&extract, "http://xmlns.com/foaf/0.1/Person");
+        let a = id(&extract, "http://www.example.com/iri#A");
+        let external = extract
+            .nodes()
+            .iter()
+            .filter_map(|node| match node {
+                Node::ExternalClass(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(external, vec![person]);
+        let properties = extract
+            .edges()
+            .iter()
+            .filter(|edge| matches!(edge, Edge::ExternalProperty(..)))
+            .count();
+        assert_eq!(properties, 2);
+        assert!(!extract.is_external("http://www.example.com/iri#A"));
+        assert!(extract.is_external("http://www.example.com/iri2#A"));
+        assert!(object_properties(&extract).is_empty());
+        assert!(!extract.nodes().iter().any(|n| matches!(n, Node::ExternalClass(id) if *id == a)));
+    }
+
+    #[test]
+    fn test_external_internal_namespaces() {
+        let extract = external(ExtractConfiguration {
+            internal_namespaces: vec!["http://xmlns.com/foaf/0.1/".to_string()],
+            ..Default::default()
+        });
+        assert!(!extract.is_external("http://xmlns.com/foaf/0.1/Person"));
+        assert_eq!(object_properties(&extract).len(), 1);
+        assert!(
+            extract
+                .edges()
+                .iter()
+                .any(|edge| matches!(edge, Edge::ExternalProperty(..)))
+        );
+    }
+
+    #[test]
+    fn test_inverse_properties() {
+        let extract = extract("data/owl-functional/inverse-properties.ofn");
+        let r = id(&extract, "http://www.example.com/iri#r");
+        let s = id(&extract, "http://www.example.com/iri#s");
+        let inverses = extract
+            .edges()
+            .iter()
+            .filter_map(|edge| match edge {
+                Edge::InverseProperty(_, p, q, _) => Some((*p, *q)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(inverses == vec![(r, s)] || inverses == vec![(s, r)]);
+        assert!(object_properties(&extract).is_empty());
+    }
+
+    #[test]
+    fn test_inverse_property_assertion() {
+        let ofn = "Prefix(o:=<http://www.example.com/iri#>)
+Ontology(<http://www.example.com/iri>
+Declaration(NamedIndividual(o:i))
+Declaration(NamedIndividual(o:j))
+ObjectPropertyAssertion(ObjectInverseOf(o:r) o:i o:j)
+)";
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut ofn.as_bytes(), ParserConfiguration::default()).unwrap();
+        let extract: HornedVOWLExtract<RcStr> = ontology.into();
+        assert_eq!(
+            object_properties(&extract),
+            vec![(
+                id(&extract, "http://www.example.com/iri#j"),
+                id(&extract, "http://www.example.com/iri#r"),
+                id(&extract, "http://www.example.com/iri#i")
+            )]
+        );
+    }
+
+    #[test]
+    fn test_inverse_properties_keep_assertions() {
+        let ofn = "Prefix(o:=<http://www.example.com/iri#>)
+Ontology(<http://www.example.com/iri>
+Declaration(ObjectProperty(o:r))
+Declaration(ObjectProperty(o:s))
+Declaration(NamedIndividual(o:i))
+Declaration(NamedIndividual(o:j))
+InverseObjectProperties(o:r o:s)
+ObjectPropertyAssertion(o:s o:i o:j)
+)";
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut ofn.as_bytes(), ParserConfiguration::default()).unwrap();
+        let extract: HornedVOWLExtract<RcStr> = ontology.into();
+        assert_eq!(
+            object_properties(&extract),
+            vec![(
+                id(&extract, "http://www.example.com/iri#i"),
+                id(&extract, "http://www.example.com/iri#s"),
+                id(&extract, "http://www.example.com/iri#j")
+            )]
+        );
+        assert_eq!(
+            extract
+                .edges()
+                .iter()
+                .filter(|edge| matches!(edge, Edge::InverseProperty(..)))
+                .count(),
+            1
+        );
+    }
+
+    const LABELS: &str = "Prefix(o:=<http://www.example.com/iri#>)
+Prefix(rdfs:=<http://www.w3.org/2000/01/rdf-schema#>)
+Prefix(skos:=<http://www.w3.org/2004/02/skos/core#>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(o:A))
+Declaration(Class(o:B))
+Declaration(Class(o:C))
+AnnotationAssertion(rdfs:label o:A \"Cat\"@en)
+AnnotationAssertion(rdfs:label o:A \"Chat\"@fr)
+AnnotationAssertion(skos:prefLabel o:A \"Katze\"@de)
+AnnotationAssertion(rdfs:comment o:A \"A small feline\"@en)
+AnnotationAssertion(skos:definition o:A \"Ein kleines Raubtier\"@de)
+AnnotationAssertion(rdfs:label o:B \"Dog\")
+)";
+
+    fn labels(languages: &[&str]) -> HornedVOWLExtract<RcStr> {
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut LABELS.as_bytes(), ParserConfiguration::default()).unwrap();
+        HornedVOWLExtract::with_config(
+            &ontology,
+            ExtractConfiguration {
+                languages: languages.iter().map(|l| l.to_string()).collect(),
+                ..Default::default()
+            },
         )
     }
+
+    #[test]
+    fn test_annotations() {
+        let extract = labels(&[]);
+        let annotations = extract.annotations("http://www.example.com/iri#A").unwrap();
+        assert_eq!(annotations.label["en"], "Cat");
+        assert_eq!(annotations.label["fr"], "Chat");
+        assert_eq!(annotations.pref_label["de"], "Katze");
+        assert_eq!(annotations.comment["en"], "A small feline");
+        assert_eq!(annotations.definition["de"], "Ein kleines Raubtier");
+        let annotations = extract.annotations("http://www.example.com/iri#B").unwrap();
+        assert_eq!(annotations.label[UNDEFINED_LANGUAGE], "Dog");
+    }
+
+    #[test]
+    fn test_label_fallback() {
+        let extract = labels(&["fr", "en"]);
+        assert_eq!(extract.label("http://www.example.com/iri#A"), "Chat");
+        assert_eq!(extract.label("http://www.example.com/iri#B"), "Dog");
+        assert_eq!(extract.label("http://www.example.com/iri#C"), "C");
+        assert_eq!(extract.comment("http://www.example.com/iri#A"), Some("A small feline"));
+
+        let extract = labels(&["de"]);
+        assert_eq!(extract.label("http://www.example.com/iri#A"), "Katze");
+        assert_eq!(extract.comment("http://www.example.com/iri#A"), Some("Ein kleines Raubtier"));
+
+        let extract = labels(&[]);
+        assert_eq!(extract.label("http://www.example.com/iri#A"), "Katze");
+        assert_eq!(extract.comment("http://www.example.com/iri#C"), None);
+    }
 }
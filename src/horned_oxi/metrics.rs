@@ -0,0 +1,298 @@
+//! Ontology metrics, gathered by a `Visit` that `HornedVOWLExtract` drives
+//! from its own walk over the components.
+use horned_owl::model::{
+    AnonymousIndividual, ClassExpression, Component, ComponentKind, DataRange, ForIRI, Kinded,
+    ObjectPropertyExpression, SubClassOf,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::horned_oxi::{horned_oxi::Kind, horned_oxi_visitor::Visit};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OntologyMetrics {
+    /// Declarations per entity type, such as `Class` or `NamedIndividual`.
+    pub declarations: BTreeMap<String, usize>,
+    /// Axioms per component kind, such as `SubClassOf`. Ontology metadata
+    /// such as the ontology IRI or imports is not counted.
+    pub axioms: BTreeMap<String, usize>,
+    /// The longest chain of `SubClassOf` axioms between named classes.
+    pub max_subclass_depth: usize,
+    /// The mean number of direct named subclasses of the named classes
+    /// that have any.
+    pub average_branching_factor: f64,
+    pub anonymous_class_expressions: usize,
+    /// Inverse object properties.
+    pub anonymous_object_property_expressions: usize,
+    /// Data ranges other than a named datatype.
+    pub anonymous_data_ranges: usize,
+    /// Distinct anonymous individuals.
+    pub anonymous_individuals: usize,
+}
+
+impl OntologyMetrics {
+    pub fn axiom_count(&self) -> usize {
+        self.axioms.values().sum()
+    }
+
+    pub fn declaration_count(&self, entity: &str) -> usize {
+        self.declarations.get(entity).copied().unwrap_or(0)
+    }
+}
+
+/// Collects [`OntologyMetrics`] while walked; see
+/// [`MetricsVisitor::into_metrics`].
+#[derive(Debug)]
+pub struct MetricsVisitor<A> {
+    metrics: OntologyMetrics,
+    // sub class -> direct named super classes
+    superclasses: BTreeMap<A, BTreeSet<A>>,
+    anonymous_individuals: BTreeSet<A>,
+}
+
+impl<A> Default for MetricsVisitor<A> {
+    fn default() -> Self {
+        Self {
+            metrics: OntologyMetrics::default(),
+            superclasses: BTreeMap::new(),
+            anonymous_individuals: BTreeSet::new(),
+        }
+    }
+}
+
+impl<A: ForIRI> MetricsVisitor<A> {
+    /// The metrics of everything walked so far, with the subclass depth
+    /// and branching factor computed from the hierarchy.
+    pub fn into_metrics(self) -> OntologyMetrics {
+        let mut metrics = self.metrics;
+        metrics.anonymous_individuals = self.anonymous_individuals.len();
+
+        let mut depths = BTreeMap::new();
+        metrics.max_subclass_depth = self
+            .superclasses
+            .keys()
+            .map(|class| depth_of(class, &self.superclasses, &mut depths, &mut BTreeSet::new()))
+            .max()
+            .unwrap_or(0);
+
+        let mut subclasses: BTreeMap<&A, usize> = BTreeMap::new();
+        for superclass in self.superclasses.values().flatten() {
+            *subclasses.entry(superclass).or_default() += 1;
+        }
+        if !subclasses.is_empty() {
+            metrics.average_branching_factor =
+                subclasses.values().sum::<usize>() as f64 / subclasses.len() as f64;
+        }
+        metrics
+    }
+}
+
+/// The longest path of superclasses above `class`. Edges back to a class
+/// already on the path are left out, so that cycles end.
+fn depth_of<'a, A: ForIRI>(
+    class: &'a A,
+    superclasses: &'a BTreeMap<A, BTreeSet<A>>,
+    depths: &mut BTreeMap<&'a A, usize>,
+    path: &mut BTreeSet<&'a A>,
+) -> usize {
+    if let Some(depth) = depths.get(class) {
+        return *depth;
+    }
+    path.insert(class);
+    let mut depth = 0;
+    for superclass in superclasses.get(class).into_iter().flatten() {
+        if !path.contains(superclass) {
+            depth = depth.max(1 + depth_of(superclass, superclasses, depths, path));
+        }
+    }
+    path.remove(class);
+    depths.insert(class, depth);
+    depth
+}
+
+/// The name an axiom of `kind` is counted under, or `None` for ontology
+/// metadata.
+fn axiom_name(kind: ComponentKind) -> Option<&'static str> {
+    Some(match kind {
+        ComponentKind::OntologyID
+        | ComponentKind::DocIRI
+        | ComponentKind::OntologyAnnotation
+        | ComponentKind::Import => return None,
+        ComponentKind::DeclareClass => "DeclareClass",
+        ComponentKind::DeclareObjectProperty => "DeclareObjectProperty",
+        ComponentKind::DeclareAnnotationProperty => "DeclareAnnotationProperty",
+        ComponentKind::DeclareDataProperty => "DeclareDataProperty",
+        ComponentKind::DeclareNamedIndividual => "DeclareNamedIndividual",
+        ComponentKind::DeclareDatatype => "DeclareDatatype",
+        ComponentKind::SubClassOf => "SubClassOf",
+        ComponentKind::EquivalentClasses => "EquivalentClasses",
+        ComponentKind::DisjointClasses => "DisjointClasses",
+        ComponentKind::DisjointUnion => "DisjointUnion",
+        ComponentKind::SubObjectPropertyOf => "SubObjectPropertyOf",
+        ComponentKind::EquivalentObjectProperties => "EquivalentObjectProperties",
+        ComponentKind::DisjointObjectProperties => "DisjointObjectProperties",
+        ComponentKind::InverseObjectProperties => "InverseObjectProperties",
+        ComponentKind::ObjectPropertyDomain => "ObjectPropertyDomain",
+        ComponentKind::ObjectPropertyRange => "ObjectPropertyRange",
+        ComponentKind::FunctionalObjectProperty => "FunctionalObjectProperty",
+        ComponentKind::InverseFunctionalObjectProperty => "InverseFunctionalObjectProperty",
+        ComponentKind::ReflexiveObjectProperty => "ReflexiveObjectProperty",
+        ComponentKind::IrreflexiveObjectProperty => "IrreflexiveObjectProperty",
+        ComponentKind::SymmetricObjectProperty => "SymmetricObjectProperty",
+        ComponentKind::AsymmetricObjectProperty => "AsymmetricObjectProperty",
+        ComponentKind::TransitiveObjectProperty => "TransitiveObjectProperty",
+        ComponentKind::SubDataPropertyOf => "SubDataPropertyOf",
+        ComponentKind::EquivalentDataProperties => "EquivalentDataProperties",
+        ComponentKind::DisjointDataProperties => "DisjointDataProperties",
+        ComponentKind::DataPropertyDomain => "DataPropertyDomain",
+        ComponentKind::DataPropertyRange => "DataPropertyRange",
+        ComponentKind::FunctionalDataProperty => "FunctionalDataProperty",
+        ComponentKind::DatatypeDefinition => "DatatypeDefinition",
+        ComponentKind::HasKey => "HasKey",
+        ComponentKind::SameIndividual => "SameIndividual",
+        ComponentKind::DifferentIndividuals => "DifferentIndividuals",
+        ComponentKind::ClassAssertion => "ClassAssertion",
+        ComponentKind::ObjectPropertyAssertion => "ObjectPropertyAssertion",
+        ComponentKind::NegativeObjectPropertyAssertion => "NegativeObjectPropertyAssertion",
+        ComponentKind::DataPropertyAssertion => "DataPropertyAssertion",
+        ComponentKind::NegativeDataPropertyAssertion => "NegativeDataPropertyAssertion",
+        ComponentKind::AnnotationAssertion => "AnnotationAssertion",
+        ComponentKind::SubAnnotationPropertyOf => "SubAnnotationPropertyOf",
+        ComponentKind::AnnotationPropertyDomain => "AnnotationPropertyDomain",
+        ComponentKind::AnnotationPropertyRange => "AnnotationPropertyRange",
+        ComponentKind::Rule => "Rule",
+    })
+}
+
+fn increment(counts: &mut BTreeMap<String, usize>, key: &str) {
+    *counts.entry(key.to_string()).or_default() += 1;
+}
+
+impl<A: ForIRI> Visit<A, u32> for MetricsVisitor<A> {
+    fn visit_component(&mut self, _: Option<Kind<u32>>, cmp: &Component<A>) -> Option<Kind<u32>> {
+        let kind = axiom_name(cmp.kind())?;
+        increment(&mut self.metrics.axioms, kind);
+        let entity = match cmp {
+            Component::DeclareClass(_) => "Class",
+            Component::DeclareObjectProperty(_) => "ObjectProperty",
+            Component::DeclareDataProperty(_) => "DataProperty",
+            Component::DeclareAnnotationProperty(_) => "AnnotationProperty",
+            Component::DeclareNamedIndividual(_) => "NamedIndividual",
+            Component::DeclareDatatype(_) => "Datatype",
+            _ => return None,
+        };
+        increment(&mut self.metrics.declarations, entity);
+        None
+    }
+
+    fn visit_sub_class_of(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &SubClassOf<A>,
+    ) -> Option<Kind<u32>> {
+        if let (ClassExpression::Class(sub), ClassExpression::Class(sup)) = (&cmp.sub, &cmp.sup) {
+            self.superclasses
+                .entry(sub.0.underlying())
+                .or_default()
+                .insert(sup.0.underlying());
+        }
+        None
+    }
+
+    fn visit_class_expression(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &ClassExpression<A>,
+    ) -> Option<Kind<u32>> {
+        if !matches!(cmp, ClassExpression::Class(_)) {
+            self.metrics.anonymous_class_expressions += 1;
+        }
+        None
+    }
+
+    fn visit_object_property_expression(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &ObjectPropertyExpression<A>,
+    ) -> Option<Kind<u32>> {
+        if let ObjectPropertyExpression::InverseObjectProperty(_) = cmp {
+            self.metrics.anonymous_object_property_expressions += 1;
+        }
+        None
+    }
+
+    fn visit_data_range(&mut self, _: Option<Kind<u32>>, cmp: &DataRange<A>) -> Option<Kind<u32>> {
+        if !matches!(cmp, DataRange::Datatype(_)) {
+            self.metrics.anonymous_data_ranges += 1;
+        }
+        None
+    }
+
+    fn visit_anonymous_individual(
+        &mut self,
+        _: Option<Kind<u32>>,
+        cmp: &AnonymousIndividual<A>,
+    ) -> Option<Kind<u32>> {
+        self.anonymous_individuals.insert(cmp.0.clone());
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::horned_oxi::horned_oxi::HornedVOWLExtract;
+    use horned_owl::{
+        io::{ParserConfiguration, ofn},
+        model::RcStr,
+        ontology::set::SetOntology,
+    };
+    use std::io::Cursor;
+
+    const ONTOLOGY: &str = "Prefix(:=<http://www.example.com/iri#>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(:A))
+Declaration(Class(:B))
+Declaration(Class(:C))
+Declaration(Class(:D))
+Declaration(ObjectProperty(:r))
+Declaration(NamedIndividual(:i))
+SubClassOf(:A :B)
+SubClassOf(:B :C)
+SubClassOf(:D :C)
+SubClassOf(:D ObjectSomeValuesFrom(ObjectInverseOf(:r) ObjectUnionOf(:A :B)))
+ClassAssertion(:A _:x)
+ObjectPropertyAssertion(:r _:x _:y)
+ObjectPropertyAssertion(:r _:x :i)
+)";
+
+    #[test]
+    fn test_metrics() {
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut Cursor::new(ONTOLOGY), ParserConfiguration::default()).unwrap();
+        let extract = HornedVOWLExtract::from(ontology);
+        let metrics = extract.metrics();
+
+        assert_eq!(metrics.declaration_count("Class"), 4);
+        assert_eq!(metrics.declaration_count("ObjectProperty"), 1);
+        assert_eq!(metrics.declaration_count("NamedIndividual"), 1);
+        assert_eq!(metrics.declaration_count("DataProperty"), 0);
+        assert_eq!(metrics.axioms["SubClassOf"], 4);
+        assert_eq!(metrics.axioms["ObjectPropertyAssertion"], 2);
+        assert_eq!(metrics.axiom_count(), 13);
+        // A -> B -> C
+        assert_eq!(metrics.max_subclass_depth, 2);
+        // C has B and D, B has A.
+        assert_eq!(metrics.average_branching_factor, 1.5);
+        assert_eq!(metrics.anonymous_class_expressions, 2);
+        assert_eq!(metrics.anonymous_object_property_expressions, 1);
+        assert_eq!(metrics.anonymous_data_ranges, 0);
+        assert_eq!(metrics.anonymous_individuals, 2);
+
+        let json = serde_json::to_value(extract.to_vowl()).unwrap();
+        assert_eq!(json["metrics"]["axiomCount"], 13);
+        assert_eq!(json["metrics"]["individualCount"], 1);
+        assert_eq!(json["metrics"]["maxSubclassDepth"], 2);
+        assert_eq!(json["metrics"]["declarations"]["Class"], 4);
+    }
+}
@@ -1,3 +1,7 @@
 pub mod errors;
+#[allow(clippy::module_inception)]
 pub mod horned_oxi;
 pub mod horned_oxi_visitor;
+pub mod metrics;
+pub mod sparql;
+pub mod vowl;
\ No newline at end of file
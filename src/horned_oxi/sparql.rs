@@ -0,0 +1,1210 @@
+//! Reading the axioms drawn by [`HornedVOWLExtract`] straight from a named
+//! graph of an Oxigraph store, instead of serializing the graph to RDF/XML
+//! and parsing it again with horned-owl.
+//!
+//! Top level axioms are found with SPARQL queries over the graph; the blank
+//! nodes of class expressions and data ranges are then looked up directly.
+//! The mapping follows the horned-owl RDF reader for the constructs that
+//! are drawn, so that both paths give the same nodes and edges.
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use horned_owl::{
+    model::{
+        Annotation, AnnotationAssertion, Build, ClassAssertion, ClassExpression, Component,
+        DataProperty, DataPropertyDomain, DataPropertyRange, DataRange, DeclareAnnotationProperty,
+        DeclareClass, DeclareDataProperty, DeclareDatatype, DeclareNamedIndividual,
+        DeclareObjectProperty, DifferentIndividuals, DisjointClasses, DisjointUnion,
+        EquivalentClasses, FacetRestriction, ForIRI, Individual, InverseObjectProperties, Literal,
+        NegativeDataPropertyAssertion, NegativeObjectPropertyAssertion, ObjectPropertyAssertion,
+        ObjectPropertyDomain, ObjectPropertyExpression, ObjectPropertyRange, OntologyID,
+        SameIndividual, SubClassOf,
+    },
+    vocab::{AnnotationBuiltIn, Facet, OWL, OWL2Datatype, Vocab},
+};
+use oxigraph::{
+    model::{BlankNode, NamedNode, NamedNodeRef, Term, vocab::xsd},
+    sparql::{QueryResults, SparqlEvaluator},
+    store::Store,
+};
+
+use crate::horned_oxi::{
+    errors::HornedOxiError,
+    horned_oxi::{ExtractConfiguration, HornedVOWLExtract},
+};
+
+const PREFIXES: &str = "PREFIX owl: <http://www.w3.org/2002/07/owl#>
+PREFIX rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#>
+PREFIX rdfs: <http://www.w3.org/2000/01/rdf-schema#>
+";
+
+const OWL_NS: &str = "http://www.w3.org/2002/07/owl#";
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const RDFS: &str = "http://www.w3.org/2000/01/rdf-schema#";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// What an IRI is declared as, in the sense of the horned-owl reader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Declared {
+    Class,
+    ObjectProperty,
+    DataProperty,
+    AnnotationProperty,
+    NamedIndividual,
+    Datatype,
+}
+
+/// Reads the components of one named graph that [`HornedVOWLExtract`]
+/// draws.
+pub struct SparqlReader<'a, A: ForIRI> {
+    store: &'a Store,
+    graph: NamedNode,
+    build: Build<A>,
+    declared: HashMap<String, Declared>,
+    /// IRIs declared both as a class and as an individual.
+    puns: HashSet<String>,
+    ontology_iri: Option<String>,
+    rdfs_classes: BTreeSet<String>,
+}
+
+impl<'a, A: ForIRI> SparqlReader<'a, A> {
+    pub fn new(store: &'a Store, graph: NamedNodeRef<'_>) -> Self {
+        Self {
+            store,
+            graph: graph.into_owned(),
+            build: Build::new(),
+            declared: HashMap::new(),
+            puns: HashSet::new(),
+            ontology_iri: None,
+            rdfs_classes: BTreeSet::new(),
+        }
+    }
+
+    /// Read the graph and extract it.
+    pub fn extract(
+        mut self,
+        config: ExtractConfiguration,
+    ) -> Result<HornedVOWLExtract<A>, HornedOxiError> {
+        let components = self.components()?;
+        let rdfs_classes = self
+            .rdfs_classes
+            .iter()
+            .map(|iri| self.build.iri(iri.as_str()).underlying())
+            .collect::<Vec<_>>();
+        Ok(HornedVOWLExtract::from_components(
+            &components,
+            rdfs_classes,
+            config,
+        ))
+    }
+
+    /// The components of the graph that are drawn, without duplicates.
+    pub fn components(&mut self) -> Result<BTreeSet<Component<A>>, HornedOxiError> {
+        let mut components = BTreeSet::new();
+        self.headers(&mut components)?;
+        self.declarations(&mut components)?;
+        self.rdfs_vocabulary(&mut components)?;
+        self.annotations(&mut components)?;
+        self.class_axioms(&mut components)?;
+        self.property_axioms(&mut components)?;
+        self.assertions(&mut components)?;
+        Ok(components)
+    }
+
+    /// The solutions of `pattern` matched against the graph, with the
+    /// values of `variables` in order.
+    fn select(
+        &self,
+        variables: &[&str],
+        pattern: &str,
+    ) -> Result<Vec<Vec<Option<Term>>>, HornedOxiError> {
+        let query = format!(
+            "{}SELECT {} WHERE {{ GRAPH {} {{ {} }} }}",
+            PREFIXES,
+            variables
+                .iter()
+                .map(|v| format!("?{}", v))
+                .collect::<Vec<_>>()
+                .join(" "),
+            self.graph,
+            pattern
+        );
+        let QueryResults::Solutions(solutions) = SparqlEvaluator::new()
+            .parse_query(&query)?
+            .on_store(self.store)
+            .execute()?
+        else {
+            return Ok(vec![]);
+        };
+        let mut rows = Vec::new();
+        for solution in solutions {
+            let solution = solution?;
+            rows.push(
+                variables
+                    .iter()
+                    .map(|v| solution.get(*v).cloned())
+                    .collect(),
+            );
+        }
+        Ok(rows)
+    }
+
+    /// The predicates and objects of a blank node, sorted by predicate.
+    fn describe(&self, node: &BlankNode) -> Result<Vec<(String, Term)>, HornedOxiError> {
+        let mut description = self
+            .store
+            .quads_for_pattern(
+                Some(node.as_ref().into()),
+                None,
+                None,
+                Some(self.graph.as_ref().into()),
+            )
+            .map(|quad| quad.map(|quad| (quad.predicate.into_string(), quad.object)))
+            .collect::<Result<Vec<_>, _>>()?;
+        description.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(description)
+    }
+
+    /// The members of the RDF list starting at `head`.
+    fn list(&self, head: &Term) -> Result<Option<Vec<Term>>, HornedOxiError> {
+        let mut members = Vec::new();
+        let mut seen = HashSet::new();
+        let mut node = head.clone();
+        loop {
+            let Term::BlankNode(bnode) = &node else {
+                return Ok(match &node {
+                    Term::NamedNode(nil) if nil.as_str() == format!("{}nil", RDF) => Some(members),
+                    _ => None,
+                });
+            };
+            if !seen.insert(bnode.clone()) {
+                return Ok(None);
+            }
+            let description = self.describe(bnode)?;
+            let (Some(first), Some(rest)) = (
+                value(&description, RDF, "first"),
+                value(&description, RDF, "rest"),
+            ) else {
+                return Ok(None);
+            };
+            members.push(first.clone());
+            node = rest.clone();
+        }
+    }
+
+    fn headers(&mut self, components: &mut BTreeSet<Component<A>>) -> Result<(), HornedOxiError> {
+        let rows = self.select(
+            &["o", "v"],
+            "?o a owl:Ontology FILTER isIRI(?o) OPTIONAL { ?o owl:versionIRI ?v FILTER isIRI(?v) }",
+        )?;
+        let mut rows = rows.into_iter().filter_map(|row| match row.as_slice() {
+            [Some(Term::NamedNode(o)), v] => Some((o.as_str().to_string(), v.clone())),
+            _ => None,
+        });
+        let (iri, viri) = match rows.next() {
+            Some((iri, Some(Term::NamedNode(viri)))) => (Some(iri), Some(viri.into_string())),
+            Some((iri, _)) => (Some(iri), None),
+            None => (None, None),
+        };
+        components.insert(
+            OntologyID {
+                iri: iri.as_deref().map(|iri| self.build.iri(iri)),
+                viri: viri.as_deref().map(|viri| self.build.iri(viri)),
+            }
+            .into(),
+        );
+        self.ontology_iri = iri;
+        Ok(())
+    }
+
+    fn declarations(
+        &mut self,
+        components: &mut BTreeSet<Component<A>>,
+    ) -> Result<(), HornedOxiError> {
+        let rows = self.select(
+            &["s", "t"],
+            "?s a ?t FILTER(isIRI(?s) && ?t IN (owl:Class, owl:ObjectProperty, \
+             owl:DatatypeProperty, owl:AnnotationProperty, owl:NamedIndividual, rdfs:Datatype))",
+        )?;
+        for row in rows {
+            let [Some(Term::NamedNode(s)), Some(Term::NamedNode(t))] = row.as_slice() else {
+                continue;
+            };
+            let iri = s.as_str();
+            if is_vocabulary(iri) {
+                continue;
+            }
+            let (declared, component): (_, Component<A>) = match t.as_str() {
+                t if t == OWL::Class.as_ref() => {
+                    (Declared::Class, DeclareClass(self.build.class(iri)).into())
+                }
+                t if t == OWL::ObjectProperty.as_ref() => (
+                    Declared::ObjectProperty,
+                    DeclareObjectProperty(self.build.object_property(iri)).into(),
+                ),
+                t if t == OWL::DatatypeProperty.as_ref() => (
+                    Declared::DataProperty,
+                    DeclareDataProperty(self.build.data_property(iri)).into(),
+                ),
+                t if t == OWL::AnnotationProperty.as_ref() => (
+                    Declared::AnnotationProperty,
+                    DeclareAnnotationProperty(self.build.annotation_property(iri)).into(),
+                ),
+                t if t == OWL::NamedIndividual.as_ref() => (
+                    Declared::NamedIndividual,
+                    DeclareNamedIndividual(self.build.named_individual(iri)).into(),
+                ),
+                _ => (
+                    Declared::Datatype,
+                    DeclareDatatype(self.build.datatype(iri)).into(),
+                ),
+            };
+            self.declare(iri, declared);
+            components.insert(component);
+        }
+        Ok(())
+    }
+
+    fn declare(&mut self, iri: &str, declared: Declared) {
+        match (self.declared.get(iri), declared) {
+            (Some(Declared::Class), Declared::NamedIndividual) => {
+                self.puns.insert(iri.to_string());
+            }
+            (Some(Declared::NamedIndividual), Declared::Class) => {
+                self.puns.insert(iri.to_string());
+                self.declared.insert(iri.to_string(), declared);
+            }
+            _ => {
+                self.declared.insert(iri.to_string(), declared);
+            }
+        }
+    }
+
+    /// Classes and properties of vocabularies that only use RDFS, which
+    /// are drawn like their OWL counterparts.
+    fn rdfs_vocabulary(
+        &mut self,
+        components: &mut BTreeSet<Component<A>>,
+    ) -> Result<(), HornedOxiError> {
+        for row in self.select(&["s"], "?s a rdfs:Class FILTER isIRI(?s)")? {
+            let [Some(Term::NamedNode(s))] = row.as_slice() else {
+                continue;
+            };
+            let iri = s.as_str();
+            if is_vocabulary(iri) || self.declared.contains_key(iri) {
+                continue;
+            }
+            self.declared.insert(iri.to_string(), Declared::Class);
+            self.rdfs_classes.insert(iri.to_string());
+        }
+        let mut properties: HashMap<String, bool> = HashMap::new();
+        for row in self.select(
+            &["p", "r"],
+            "?p a rdf:Property FILTER isIRI(?p) OPTIONAL { ?p rdfs:range ?r }",
+        )? {
+            let [Some(Term::NamedNode(p)), range] = row.as_slice() else {
+                continue;
+            };
+            let data = match range {
+                Some(Term::NamedNode(range)) => self.is_datatype(range.as_str()),
+                _ => false,
+            };
+            *properties.entry(p.as_str().to_string()).or_default() |= data;
+        }
+        for (iri, data) in properties {
+            if is_vocabulary(&iri) || self.declared.contains_key(&iri) {
+                continue;
+            }
+            if data {
+                self.declared.insert(iri.clone(), Declared::DataProperty);
+                components.insert(DeclareDataProperty(self.build.data_property(iri)).into());
+            } else {
+                self.declared.insert(iri.clone(), Declared::ObjectProperty);
+                components.insert(DeclareObjectProperty(self.build.object_property(iri)).into());
+            }
+        }
+        Ok(())
+    }
+
+    fn is_datatype(&self, iri: &str) -> bool {
+        iri.starts_with(XSD)
+            || iri == OWL2Datatype::Literal.as_ref()
+            || self.declared.get(iri) == Some(&Declared::Datatype)
+    }
+
+    /// Annotation assertions with literal values on IRIs, which carry the
+    /// labels, comments and deprecation of entities.
+    fn annotations(
+        &mut self,
+        components: &mut BTreeSet<Component<A>>,
+    ) -> Result<(), HornedOxiError> {
+        let rows = self.select(
+            &["s", "p", "o"],
+            "?s ?p ?o FILTER(isIRI(?s) && isLiteral(?o))",
+        )?;
+        for row in rows {
+            let [
+                Some(Term::NamedNode(s)),
+                Some(Term::NamedNode(p)),
+                Some(Term::Literal(o)),
+            ] = row.as_slice()
+            else {
+                continue;
+            };
+            let (subject, property) = (s.as_str(), p.as_str());
+            // Anything said about the ontology is an ontology annotation.
+            if is_vocabulary(subject) || self.ontology_iri.as_deref() == Some(subject) {
+                continue;
+            }
+            let builtin = property
+                .parse::<AnnotationBuiltIn>()
+                .is_ok_and(|_| property.starts_with(RDFS) || !is_vocabulary(property));
+            let declared = !is_vocabulary(property)
+                && self.declared.get(property) == Some(&Declared::AnnotationProperty);
+            if !(builtin || declared) {
+                continue;
+            }
+            components.insert(
+                AnnotationAssertion {
+                    subject: self.build.iri(subject).into(),
+                    ann: Annotation {
+                        ap: self.build.annotation_property(property),
+                        av: self.literal(o).into(),
+                    },
+                }
+                .into(),
+            );
+        }
+        Ok(())
+    }
+
+    fn class_axioms(
+        &mut self,
+        components: &mut BTreeSet<Component<A>>,
+    ) -> Result<(), HornedOxiError> {
+        let rows = self.select(
+            &["s", "p", "o"],
+            "?s ?p ?o FILTER(?p IN (rdfs:subClassOf, owl:equivalentClass, owl:disjointWith, \
+             owl:disjointUnionOf))",
+        )?;
+        for row in rows {
+            let [Some(s), Some(Term::NamedNode(p)), Some(o)] = row.as_slice() else {
+                continue;
+            };
+            let component: Option<Component<A>> = match p.as_str() {
+                p if p == format!("{}subClassOf", RDFS) => {
+                    match (self.class_expression(s)?, self.class_expression(o)?) {
+                        (Some(sub), Some(sup)) => Some(SubClassOf { sub, sup }.into()),
+                        _ => None,
+                    }
+                }
+                p if p == OWL::EquivalentClass.as_ref() => match s {
+                    // Equivalent datatypes define a datatype; they are not
+                    // drawn.
+                    Term::NamedNode(iri) if self.is_datatype(iri.as_str()) => None,
+                    _ => match (self.class_expression(s)?, self.class_expression(o)?) {
+                        (Some(s), Some(o)) => Some(EquivalentClasses(vec![s, o]).into()),
+                        _ => None,
+                    },
+                },
+                p if p == OWL::DisjointWith.as_ref() => {
+                    match (self.class_expression(s)?, self.class_expression(o)?) {
+                        (Some(s), Some(o)) => Some(DisjointClasses(vec![s, o]).into()),
+                        _ => None,
+                    }
+                }
+                _ => match (s, o) {
+                    (Term::NamedNode(class), Term::BlankNode(_))
+                        if !is_vocabulary(class.as_str()) =>
+                    {
+                        self.class_expressions(o)?.map(|operands| {
+                            DisjointUnion(self.build.class(class.as_str()), operands).into()
+                        })
+                    }
+                    _ => None,
+                },
+            };
+            components.extend(component);
+        }
+        Ok(())
+    }
+
+    fn property_axioms(
+        &mut self,
+        components: &mut BTreeSet<Component<A>>,
+    ) -> Result<(), HornedOxiError> {
+        let rows = self.select(
+            &["s", "p", "o"],
+            "?s ?p ?o FILTER(?p IN (rdfs:domain, rdfs:range, owl:inverseOf))",
+        )?;
+        for row in rows {
+            let [Some(s), Some(Term::NamedNode(p)), Some(o)] = row.as_slice() else {
+                continue;
+            };
+            let domain = p.as_str() == format!("{}domain", RDFS);
+            let component: Option<Component<A>> = match p.as_str() {
+                p if p == OWL::InverseOf.as_ref() => match (s, o) {
+                    (Term::NamedNode(p), Term::NamedNode(q))
+                        if !is_vocabulary(p.as_str()) && !is_vocabulary(q.as_str()) =>
+                    {
+                        Some(
+                            InverseObjectProperties(
+                                self.build.object_property(p.as_str()),
+                                self.build.object_property(q.as_str()),
+                            )
+                            .into(),
+                        )
+                    }
+                    _ => None,
+                },
+                _ => match self.property(s)? {
+                    Some(Property::Object(ope)) => self.class_expression(o)?.map(|ce| {
+                        if domain {
+                            ObjectPropertyDomain { ope, ce }.into()
+                        } else {
+                            ObjectPropertyRange { ope, ce }.into()
+                        }
+                    }),
+                    Some(Property::Data(dp)) if domain => self
+                        .class_expression(o)?
+                        .map(|ce| DataPropertyDomain { dp, ce }.into()),
+                    Some(Property::Data(dp)) => self
+                        .data_range(o)?
+                        .map(|dr| DataPropertyRange { dp, dr }.into()),
+                    None => None,
+                },
+            };
+            components.extend(component);
+        }
+        Ok(())
+    }
+
+    /// Assertions about individuals, each of which draws the individuals
+    /// it names.
+    fn assertions(
+        &mut self,
+        components: &mut BTreeSet<Component<A>>,
+    ) -> Result<(), HornedOxiError> {
+        let rows = self.select(
+            &["s", "p", "o"],
+            "?p a owl:ObjectProperty . ?s ?p ?o FILTER(isIRI(?s) && isIRI(?o))",
+        )?;
+        for row in rows {
+            let [
+                Some(Term::NamedNode(s)),
+                Some(Term::NamedNode(p)),
+                Some(Term::NamedNode(o)),
+            ] = row.as_slice()
+            else {
+                continue;
+            };
+            if self.is_individual(s.as_str())
+                && self.declared.get(p.as_str()) == Some(&Declared::ObjectProperty)
+                && self.is_individual(o.as_str())
+            {
+                components.insert(
+                    ObjectPropertyAssertion {
+                        ope: self.build.object_property(p.as_str()).into(),
+                        from: self.individual(s),
+                        to: self.individual(o),
+                    }
+                    .into(),
+                );
+            }
+        }
+
+        let rows = self.select(
+            &["s", "c"],
+            "?s a ?c FILTER(isIRI(?s) && ?c NOT IN (owl:Class, owl:ObjectProperty, \
+             owl:DatatypeProperty, owl:AnnotationProperty, owl:NamedIndividual, rdfs:Datatype, \
+             owl:Ontology))",
+        )?;
+        for row in rows {
+            let [Some(Term::NamedNode(s)), Some(c)] = row.as_slice() else {
+                continue;
+            };
+            if is_vocabulary(s.as_str()) || self.ontology_iri.as_deref() == Some(s.as_str()) {
+                continue;
+            }
+            if let Some(ce) = self.class_expression(c)? {
+                components.insert(
+                    ClassAssertion {
+                        ce,
+                        i: self.individual(s),
+                    }
+                    .into(),
+                );
+            }
+        }
+
+        let rows = self.select(
+            &["s", "p", "o"],
+            "?s ?p ?o FILTER(isIRI(?s) && isIRI(?o) && ?p IN (owl:sameAs, owl:differentFrom))",
+        )?;
+        for row in rows {
+            let [
+                Some(Term::NamedNode(s)),
+                Some(Term::NamedNode(p)),
+                Some(Term::NamedNode(o)),
+            ] = row.as_slice()
+            else {
+                continue;
+            };
+            let individuals = vec![self.individual(s), self.individual(o)];
+            components.insert(if p.as_str() == OWL::SameAs.as_ref() {
+                SameIndividual(individuals).into()
+            } else {
+                DifferentIndividuals(individuals).into()
+            });
+        }
+
+        let rows = self.select(
+            &["s", "l"],
+            "?s a owl:AllDifferent ; owl:members|owl:distinctMembers ?l FILTER isBlank(?s)",
+        )?;
+        for row in rows {
+            let [Some(_), Some(list)] = row.as_slice() else {
+                continue;
+            };
+            if let Some(individuals) = self.individuals(list)? {
+                components.insert(DifferentIndividuals(individuals).into());
+            }
+        }
+
+        let rows = self.select(
+            &["p", "s", "t", "v"],
+            "?x a owl:NegativePropertyAssertion ; owl:assertionProperty ?p ; \
+             owl:sourceIndividual ?s . \
+             { ?x owl:targetIndividual ?t } UNION { ?x owl:targetValue ?v } \
+             FILTER isIRI(?s)",
+        )?;
+        for row in rows {
+            let [Some(p), Some(Term::NamedNode(s)), target, value] = row.as_slice() else {
+                continue;
+            };
+            let component: Option<Component<A>> = match (self.property(p)?, target, value) {
+                (Some(Property::Object(ope)), Some(Term::NamedNode(to)), _) => Some(
+                    NegativeObjectPropertyAssertion {
+                        ope,
+                        from: self.individual(s),
+                        to: self.individual(to),
+                    }
+                    .into(),
+                ),
+                (Some(Property::Data(dp)), _, Some(Term::Literal(to))) => Some(
+                    NegativeDataPropertyAssertion {
+                        dp,
+                        from: self.individual(s),
+                        to: self.literal(to),
+                    }
+                    .into(),
+                ),
+                _ => None,
+            };
+            components.extend(component);
+        }
+        Ok(())
+    }
+
+    /// Whether `iri` is declared as an individual, preferring that over a
+    /// class it is punned with.
+    fn is_individual(&self, iri: &str) -> bool {
+        self.puns.contains(iri) || self.declared.get(iri) == Some(&Declared::NamedIndividual)
+    }
+
+    fn individual(&self, iri: &NamedNode) -> Individual<A> {
+        self.build.named_individual(iri.as_str()).into()
+    }
+
+    fn individuals(&self, list: &Term) -> Result<Option<Vec<Individual<A>>>, HornedOxiError> {
+        Ok(self.list(list)?.and_then(|members| {
+            members
+                .iter()
+                .map(|member| match member {
+                    Term::NamedNode(iri) => Some(self.individual(iri)),
+                    _ => None,
+                })
+                .collect()
+        }))
+    }
+
+    /// The kind of the property `term`, by its declaration.
+    fn property(&self, term: &Term) -> Result<Option<Property<A>>, HornedOxiError> {
+        Ok(match term {
+            Term::NamedNode(iri) => match self.declared_kind(iri.as_str()) {
+                Some(Declared::ObjectProperty) => Some(Property::Object(
+                    self.build.object_property(iri.as_str()).into(),
+                )),
+                Some(Declared::DataProperty) => {
+                    Some(Property::Data(self.build.data_property(iri.as_str())))
+                }
+                _ => None,
+            },
+            Term::BlankNode(bnode) => self.inverse(bnode)?.map(Property::Object),
+            _ => None,
+        })
+    }
+
+    /// The declared kind of `iri`, including the built in entities.
+    fn declared_kind(&self, iri: &str) -> Option<Declared> {
+        self.declared.get(iri).copied().or(match iri {
+            _ if iri == OWL::TopObjectProperty.as_ref() => Some(Declared::ObjectProperty),
+            _ if iri == OWL::TopDataProperty.as_ref() => Some(Declared::DataProperty),
+            _ if iri == OWL::Thing.as_ref() || iri == OWL::Nothing.as_ref() => {
+                Some(Declared::Class)
+            }
+            _ => None,
+        })
+    }
+
+    /// The inverse of a named property, written as a blank node.
+    fn inverse(
+        &self,
+        bnode: &BlankNode,
+    ) -> Result<Option<ObjectPropertyExpression<A>>, HornedOxiError> {
+        Ok(match self.describe(bnode)?.as_slice() {
+            [(p, Term::NamedNode(iri))] if p == OWL::InverseOf.as_ref() => {
+                Some(ObjectPropertyExpression::InverseObjectProperty(
+                    self.build.object_property(iri.as_str()),
+                ))
+            }
+            _ => None,
+        })
+    }
+
+    fn class_expressions(
+        &self,
+        list: &Term,
+    ) -> Result<Option<Vec<ClassExpression<A>>>, HornedOxiError> {
+        let Some(members) = self.list(list)? else {
+            return Ok(None);
+        };
+        let members = members
+            .iter()
+            .map(|m| self.class_expression(m))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(members.into_iter().collect())
+    }
+
+    /// The class expression `term` stands for. Terms of the OWL, RDF and
+    /// RDFS vocabularies, `owl:Thing` among them, are not read as classes,
+    /// as in horned-owl.
+    fn class_expression(&self, term: &Term) -> Result<Option<ClassExpression<A>>, HornedOxiError> {
+        self.class_expression_at(term, 0)
+    }
+
+    fn class_expression_at(
+        &self,
+        term: &Term,
+        depth: usize,
+    ) -> Result<Option<ClassExpression<A>>, HornedOxiError> {
+        // Guard against cyclic blank node structures.
+        const MAX_DEPTH: usize = 64;
+        let bnode = match term {
+            Term::NamedNode(iri) if !is_vocabulary(iri.as_str()) => {
+                return Ok(Some(self.build.class(iri.as_str()).into()));
+            }
+            Term::BlankNode(bnode) if depth < MAX_DEPTH => bnode,
+            _ => return Ok(None),
+        };
+        // A class expression may itself be the subject of axioms, as in a
+        // general class inclusion; those triples are not part of its shape.
+        let description = self
+            .describe(bnode)?
+            .into_iter()
+            .filter(|(p, _)| {
+                *p != format!("{}subClassOf", RDFS)
+                    && p != OWL::EquivalentClass.as_ref()
+                    && p != OWL::DisjointWith.as_ref()
+            })
+            .collect::<Vec<_>>();
+        let predicates = description
+            .iter()
+            .map(|(p, _)| p.as_str())
+            .collect::<Vec<_>>();
+        let owl = |name: &str| value(&description, OWL_NS, name);
+        let is = |names: &[&str]| {
+            let mut expected = names
+                .iter()
+                .map(|name| owl_iri(name))
+                .chain([format!("{}type", RDF)])
+                .collect::<Vec<_>>();
+            expected.sort();
+            expected
+                .iter()
+                .map(String::as_str)
+                .eq(predicates.iter().copied())
+        };
+        let nested = |term: &Term| self.class_expression_at(term, depth + 1);
+        let typed = |class: &str| {
+            description.iter().any(|(p, o)| {
+                *p == format!("{}type", RDF)
+                    && matches!(o, Term::NamedNode(t) if t.as_str() == class)
+            })
+        };
+        use ClassExpression as CE;
+        if typed(OWL::Class.as_ref()) {
+            let ce = if is(&["intersectionOf"]) {
+                self.class_expression_list(owl("intersectionOf"), depth)?
+                    .map(CE::ObjectIntersectionOf)
+            } else if is(&["unionOf"]) {
+                self.class_expression_list(owl("unionOf"), depth)?
+                    .map(CE::ObjectUnionOf)
+            } else if is(&["complementOf"]) {
+                nested(owl("complementOf").unwrap())?.map(|ce| CE::ObjectComplementOf(ce.into()))
+            } else if is(&["oneOf"]) {
+                self.individuals(owl("oneOf").unwrap())?
+                    .map(CE::ObjectOneOf)
+            } else {
+                None
+            };
+            return Ok(ce);
+        }
+        if !typed(OWL::Restriction.as_ref()) {
+            return Ok(None);
+        }
+        let on_property = owl("onProperty");
+        let named_property = match on_property {
+            Some(Term::NamedNode(iri)) => Some(iri.as_str()),
+            _ => None,
+        };
+        let property = match on_property {
+            Some(term) => self.property(term)?,
+            None => None,
+        };
+        let n = |name: &str| match owl(name) {
+            Some(Term::Literal(literal)) => literal.value().parse::<u32>().ok(),
+            _ => None,
+        };
+        let thing = || -> Box<CE<A>> { Box::new(self.build.class(OWL::Thing.as_ref()).into()) };
+        let literal =
+            || -> DataRange<A> { self.build.datatype(OWL2Datatype::Literal.as_ref()).into() };
+        let ce = if is(&["onProperty", "someValuesFrom"]) || is(&["onProperty", "allValuesFrom"]) {
+            let some = owl("someValuesFrom").is_some();
+            let filler = owl("someValuesFrom").or(owl("allValuesFrom")).unwrap();
+            match property {
+                Some(Property::Object(ope)) => nested(filler)?.map(|bce| {
+                    let bce = Box::new(bce);
+                    if some {
+                        CE::ObjectSomeValuesFrom { ope, bce }
+                    } else {
+                        CE::ObjectAllValuesFrom { ope, bce }
+                    }
+                }),
+                Some(Property::Data(dp)) => self.data_range(filler)?.map(|dr| {
+                    if some {
+                        CE::DataSomeValuesFrom { dp, dr }
+                    } else {
+                        CE::DataAllValuesFrom { dp, dr }
+                    }
+                }),
+                None => None,
+            }
+        } else if is(&["onProperty", "hasValue"]) {
+            match (property, owl("hasValue").unwrap()) {
+                (Some(Property::Object(ope)), Term::NamedNode(i)) => Some(CE::ObjectHasValue {
+                    ope,
+                    i: self.individual(i),
+                }),
+                (Some(Property::Data(dp)), Term::Literal(l)) => Some(CE::DataHasValue {
+                    dp,
+                    l: self.literal(l),
+                }),
+                _ => None,
+            }
+        } else if is(&["onProperty", "hasSelf"]) {
+            match property {
+                Some(Property::Object(ope)) => Some(CE::ObjectHasSelf(ope)),
+                _ => None,
+            }
+        } else if is(&["onProperty", "cardinality"]) {
+            match (property, n("cardinality")) {
+                (Some(Property::Object(ope)), Some(n)) => Some(CE::ObjectExactCardinality {
+                    n,
+                    ope,
+                    bce: thing(),
+                }),
+                (Some(Property::Data(dp)), Some(n)) => Some(CE::DataExactCardinality {
+                    n,
+                    dp,
+                    dr: literal(),
+                }),
+                _ => None,
+            }
+        } else if let Some(property) = named_property {
+            // Other cardinalities name the property, whose kind follows
+            // from the filler rather than a declaration.
+            let ope = || ObjectPropertyExpression::from(self.build.object_property(property));
+            let dp = || self.build.data_property(property);
+            let mut ce = None;
+            for (name, qualified) in [
+                ("qualifiedCardinality", true),
+                ("minQualifiedCardinality", true),
+                ("maxQualifiedCardinality", true),
+                ("minCardinality", false),
+                ("maxCardinality", false),
+            ] {
+                let Some(n) = n(name) else { continue };
+                let exact = name == "qualifiedCardinality";
+                let min = name.starts_with("min");
+                ce = if !qualified && is(&["onProperty", name]) {
+                    let (ope, bce) = (ope(), thing());
+                    Some(if min {
+                        CE::ObjectMinCardinality { n, ope, bce }
+                    } else {
+                        CE::ObjectMaxCardinality { n, ope, bce }
+                    })
+                } else if qualified && is(&["onProperty", name, "onClass"]) {
+                    nested(owl("onClass").unwrap())?.map(|bce| {
+                        let (ope, bce) = (ope(), Box::new(bce));
+                        if exact {
+                            CE::ObjectExactCardinality { n, ope, bce }
+                        } else if min {
+                            CE::ObjectMinCardinality { n, ope, bce }
+                        } else {
+                            CE::ObjectMaxCardinality { n, ope, bce }
+                        }
+                    })
+                } else if qualified && is(&["onProperty", name, "onDataRange"]) {
+                    self.data_range(owl("onDataRange").unwrap())?.map(|dr| {
+                        let dp = dp();
+                        if exact {
+                            CE::DataExactCardinality { n, dp, dr }
+                        } else if min {
+                            CE::DataMinCardinality { n, dp, dr }
+                        } else {
+                            CE::DataMaxCardinality { n, dp, dr }
+                        }
+                    })
+                } else {
+                    None
+                };
+                break;
+            }
+            ce
+        } else {
+            None
+        };
+        Ok(ce)
+    }
+
+    fn class_expression_list(
+        &self,
+        list: Option<&Term>,
+        depth: usize,
+    ) -> Result<Option<Vec<ClassExpression<A>>>, HornedOxiError> {
+        let Some(members) = list.map(|list| self.list(list)).transpose()?.flatten() else {
+            return Ok(None);
+        };
+        let mut operands = Vec::new();
+        for member in &members {
+            match self.class_expression_at(member, depth + 1)? {
+                Some(ce) => operands.push(ce),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(operands))
+    }
+
+    /// The data range `term` stands for.
+    fn data_range(&self, term: &Term) -> Result<Option<DataRange<A>>, HornedOxiError> {
+        let bnode = match term {
+            Term::NamedNode(iri) => return Ok(Some(self.build.datatype(iri.as_str()).into())),
+            Term::BlankNode(bnode) => bnode,
+            _ => return Ok(None),
+        };
+        let description = self.describe(bnode)?;
+        let datatype = description.iter().any(|(p, o)| {
+            *p == format!("{}type", RDF)
+                && matches!(o, Term::NamedNode(t) if t.as_str() == format!("{}Datatype", RDFS))
+        });
+        if !datatype || description.len() < 2 {
+            return Ok(None);
+        }
+        let owl = |name: &str| value(&description, OWL_NS, name);
+        let ranges = |list: &Term| -> Result<Option<Vec<DataRange<A>>>, HornedOxiError> {
+            let Some(members) = self.list(list)? else {
+                return Ok(None);
+            };
+            let mut ranges = Vec::new();
+            for member in &members {
+                match self.data_range(member)? {
+                    Some(dr) => ranges.push(dr),
+                    None => return Ok(None),
+                }
+            }
+            Ok(Some(ranges))
+        };
+        Ok(match description.len() {
+            2 if owl("intersectionOf").is_some() => {
+                ranges(owl("intersectionOf").unwrap())?.map(DataRange::DataIntersectionOf)
+            }
+            2 if owl("unionOf").is_some() => {
+                ranges(owl("unionOf").unwrap())?.map(DataRange::DataUnionOf)
+            }
+            2 if owl("datatypeComplementOf").is_some() => self
+                .data_range(owl("datatypeComplementOf").unwrap())?
+                .map(|dr| DataRange::DataComplementOf(dr.into())),
+            2 if owl("oneOf").is_some() => self.list(owl("oneOf").unwrap())?.and_then(|members| {
+                members
+                    .iter()
+                    .map(|member| match member {
+                        Term::Literal(literal) => Some(self.literal(literal)),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .map(DataRange::DataOneOf)
+            }),
+            3 => match (owl("onDatatype"), owl("withRestrictions")) {
+                (Some(Term::NamedNode(on)), Some(restrictions)) => {
+                    let Some(members) = self.list(restrictions)? else {
+                        return Ok(None);
+                    };
+                    let mut facets = Vec::new();
+                    for member in &members {
+                        let Term::BlankNode(member) = member else {
+                            return Ok(None);
+                        };
+                        match self.describe(member)?.as_slice() {
+                            [(facet, Term::Literal(l))] => match facet.parse::<Facet>() {
+                                Ok(f) => facets.push(FacetRestriction {
+                                    f,
+                                    l: self.literal(l),
+                                }),
+                                Err(_) => return Ok(None),
+                            },
+                            _ => return Ok(None),
+                        }
+                    }
+                    Some(DataRange::DatatypeRestriction(
+                        self.build.datatype(on.as_str()),
+                        facets,
+                    ))
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    fn literal(&self, literal: &oxigraph::model::Literal) -> Literal<A> {
+        match literal.language() {
+            Some(lang) => Literal::Language {
+                literal: literal.value().to_string(),
+                lang: lang.to_string(),
+            },
+            None if literal.datatype() == xsd::STRING => Literal::Simple {
+                literal: literal.value().to_string(),
+            },
+            None => Literal::Datatype {
+                literal: literal.value().to_string(),
+                datatype_iri: self.build.iri(literal.datatype().as_str()),
+            },
+        }
+    }
+}
+
+enum Property<A> {
+    Object(ObjectPropertyExpression<A>),
+    Data(DataProperty<A>),
+}
+
+/// Whether `iri` is a term of the vocabularies horned-owl reads as syntax
+/// rather than as entities.
+fn is_vocabulary(iri: &str) -> bool {
+    matches!(
+        Vocab::lookup(iri),
+        Some(Vocab::OWL(_) | Vocab::RDF(_) | Vocab::RDFS(_) | Vocab::SWRL(_) | Vocab::Facet(_))
+    )
+}
+
+fn owl_iri(name: &str) -> String {
+    format!("{}{}", OWL_NS, name)
+}
+
+/// The object of `namespace` + `name` in a blank node description.
+fn value<'d>(description: &'d [(String, Term)], namespace: &str, name: &str) -> Option<&'d Term> {
+    description
+        .iter()
+        .find(|(p, _)| p.strip_prefix(namespace) == Some(name))
+        .map(|(_, o)| o)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use horned_owl::model::RcStr;
+    use test_generator::test_resources;
+
+    use super::*;
+    use crate::{
+        horned_oxi::horned_oxi::{Edge, Node},
+        store::{HornedOxiStore, ResourceType, file_iri},
+    };
+
+    /// Node and edge descriptions of an extract that do not depend on the
+    /// order in which ids were handed out. Named nodes are described by
+    /// their IRI and anonymous ones by the edges leaving them.
+    fn canonical(extract: &HornedVOWLExtract<RcStr>) -> (Vec<String>, Vec<String>) {
+        let names = extract
+            .iricache()
+            .iter()
+            .map(|(iri, (id, _))| (*id, iri.to_string()))
+            .collect::<HashMap<_, _>>();
+        let edges = extract.edges().iter().map(edge_ids).collect::<Vec<_>>();
+        let describe = |id: u32| describe(id, extract, &names, &edges, &mut HashSet::new());
+        let mut nodes = extract
+            .nodes()
+            .iter()
+            .map(|node| {
+                let (kind, id) = node_id(node);
+                format!("{}({})", kind, describe(id))
+            })
+            .collect::<Vec<_>>();
+        let mut drawn = edges
+            .iter()
+            .map(|(kind, ids)| {
+                let ids = ids.iter().map(|id| describe(*id)).collect::<Vec<_>>();
+                format!("{}({})", kind, ids.join(", "))
+            })
+            .collect::<Vec<_>>();
+        nodes.sort();
+        drawn.sort();
+        (nodes, drawn)
+    }
+
+    fn describe(
+        id: u32,
+        extract: &HornedVOWLExtract<RcStr>,
+        names: &HashMap<u32, String>,
+        edges: &[(String, Vec<u32>)],
+        seen: &mut HashSet<u32>,
+    ) -> String {
+        if let Some(original) = extract.aliases().get(&id) {
+            return format!("={}", describe(*original, extract, names, edges, seen));
+        }
+        if let Some(name) = names.get(&id) {
+            return name.clone();
+        }
+        if !seen.insert(id) {
+            return "...".to_string();
+        }
+        let mut kinds = extract
+            .nodes()
+            .iter()
+            .map(node_id)
+            .filter(|(_, node)| *node == id)
+            .map(|(kind, _)| kind.to_string())
+            .collect::<Vec<_>>();
+        let mut out = edges
+            .iter()
+            .filter(|(_, ids)| ids.first() == Some(&id))
+            .map(|(kind, ids)| {
+                let ids = ids[1..]
+                    .iter()
+                    .map(|id| describe(*id, extract, names, edges, seen))
+                    .collect::<Vec<_>>();
+                format!("{}({})", kind, ids.join(", "))
+            })
+            .collect::<Vec<_>>();
+        seen.remove(&id);
+        kinds.sort();
+        out.sort();
+        format!("_[{}]{{{}}}", kinds.join(" "), out.join(" "))
+    }
+
+    fn node_id(node: &Node<u32>) -> (&'static str, u32) {
+        match node {
+            Node::Class(id) => ("Class", *id),
+            Node::ExternalClass(id) => ("ExternalClass", *id),
+            Node::Thing(id) => ("Thing", *id),
+            Node::EquivalentClass(ids) => ("EquivalentClass", ids[0]),
+            Node::Union(id) => ("Union", *id),
+            Node::DisjointUnion(id) => ("DisjointUnion", *id),
+            Node::DisjointClasses(id) => ("DisjointClasses", *id),
+            Node::Intersection(id) => ("Intersection", *id),
+            Node::Complement(id) => ("Complement", *id),
+            Node::DeprecatedClass(id) => ("DeprecatedClass", *id),
+            Node::AnonymousClass(id) => ("AnonymousClass", *id),
+            Node::Literal(id) => ("Literal", *id),
+            Node::Datatype(id) => ("Datatype", *id),
+            Node::RdfsClass(id) => ("RdfsClass", *id),
+            Node::RdfsResource(id) => ("RdfsResource", *id),
+        }
+    }
+
+    fn edge_ids(edge: &Edge<u32>) -> (String, Vec<u32>) {
+        let (kind, ids) = match edge {
+            Edge::ObjectProperty(a, b, c) => ("ObjectProperty".to_string(), vec![*a, *b, *c]),
+            Edge::DatatypeProperty(a, b, c) => ("DatatypeProperty".to_string(), vec![*a, *b, *c]),
+            Edge::SubclassOf(a, b) => ("SubclassOf".to_string(), vec![*a, *b]),
+            Edge::InverseProperty(a, b, c, d) => {
+                ("InverseProperty".to_string(), vec![*a, *b, *c, *d])
+            }
+            Edge::DisjointWith(a, b) => ("DisjointWith".to_string(), vec![*a, *b]),
+            Edge::RdfProperty(a, b) => ("RdfProperty".to_string(), vec![*a, *b]),
+            Edge::DeprecatedProperty(a, b, c) => {
+                ("DeprecatedProperty".to_string(), vec![*a, *b, *c])
+            }
+            Edge::ExternalProperty(a, b, c) => ("ExternalProperty".to_string(), vec![*a, *b, *c]),
+            Edge::ValuesFrom(a, b, c, r) => (format!("ValuesFrom[{:?}]", r), vec![*a, *b, *c]),
+            Edge::SetOperator(a, b) => ("SetOperator".to_string(), vec![*a, *b]),
+            Edge::NoDraw => ("NoDraw".to_string(), vec![]),
+        };
+        (kind, ids)
+    }
+
+    #[test_resources("data/owl-rdf/*.owl")]
+    fn test_matches_horned_extract(resource: &str) {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let path = Path::new(resource);
+        store.insert_file(path, false).unwrap();
+        let ontology = store.get_file_ontology(path).unwrap();
+        let expected = store.convert_ontology(ontology).unwrap();
+        let actual = store
+            .extract_graph(&file_iri(path).unwrap(), ExtractConfiguration::default())
+            .unwrap();
+        assert_eq!(canonical(&actual), canonical(&expected), "{}", resource);
+    }
+
+    #[test]
+    fn test_rdfs_vocabulary() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let graph = "http://www.example.com/graph";
+        store
+            .insert_bytes(
+                br#"@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix : <http://www.example.com/iri#> .
+:Person a rdfs:Class ; rdfs:label "Person" .
+:Student a rdfs:Class ; rdfs:subClassOf :Person .
+:knows a rdf:Property ; rdfs:domain :Person ; rdfs:range :Person .
+:name a rdf:Property ; rdfs:domain :Person ; rdfs:range xsd:string .
+"#,
+                ResourceType::TTL,
+                graph,
+                None,
+                false,
+            )
+            .unwrap();
+        let extract = store
+            .extract_graph(graph, ExtractConfiguration::default())
+            .unwrap();
+        let (nodes, edges) = canonical(&extract);
+        assert!(nodes.contains(&"RdfsClass(http://www.example.com/iri#Person)".to_string()));
+        assert!(nodes.contains(&"RdfsClass(http://www.example.com/iri#Student)".to_string()));
+        assert!(edges.contains(
+            &"SubclassOf(http://www.example.com/iri#Student, http://www.example.com/iri#Person)"
+                .to_string()
+        ));
+        assert!(edges.contains(
+            &"ObjectProperty(http://www.example.com/iri#Person, http://www.example.com/iri#knows, http://www.example.com/iri#Person)"
+                .to_string()
+        ));
+        assert!(edges.iter().any(|edge| edge.starts_with(
+            "DatatypeProperty(http://www.example.com/iri#Person, http://www.example.com/iri#name,"
+        )));
+        assert_eq!(extract.label("http://www.example.com/iri#Person"), "Person");
+    }
+}
@@ -0,0 +1,775 @@
+/// Serialization of a `HornedVOWLExtract` into the WebVOWL JSON schema.
+use horned_owl::model::ForIRI;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io::Write,
+};
+
+use crate::horned_oxi::{
+    errors::HornedOxiError,
+    horned_oxi::{Edge, HornedVOWLExtract, Node, Restriction},
+    metrics::OntologyMetrics,
+};
+
+const IRI_BASED: &str = "IRI-based";
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VOWLJson {
+    #[serde(rename = "_comment")]
+    pub comment: String,
+    pub header: VOWLHeader,
+    pub namespace: Vec<VOWLNamespace>,
+    pub metrics: VOWLMetrics,
+    pub class: Vec<VOWLElement>,
+    pub class_attribute: Vec<VOWLClassAttribute>,
+    pub property: Vec<VOWLElement>,
+    pub property_attribute: Vec<VOWLPropertyAttribute>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VOWLHeader {
+    pub languages: Vec<String>,
+    pub base_iris: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub title: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub description: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct VOWLNamespace {
+    pub name: String,
+    pub iri: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VOWLMetrics {
+    pub class_count: usize,
+    pub object_property_count: usize,
+    pub datatype_property_count: usize,
+    pub individual_count: usize,
+    pub axiom_count: usize,
+    pub node_count: usize,
+    pub edge_count: usize,
+    #[serde(flatten)]
+    pub ontology: OntologyMetrics,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VOWLElement {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VOWLClassAttribute {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_iri: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub label: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub comment: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub equivalent: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub union: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub disjoint_union: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub intersection: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub complement: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sub_classes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub super_classes: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VOWLPropertyAttribute {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_iri: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub label: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub comment: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+    pub domain: String,
+    pub range: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inverse: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_cardinality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_cardinality: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cardinality: Option<String>,
+}
+
+impl<T> Node<T> {
+    /// The WebVOWL `type` of this node.
+    pub fn vowl_type(&self) -> &'static str {
+        match self {
+            Node::Class(_) => "owl:Class",
+            Node::ExternalClass(_) => "owl:Class",
+            Node::Thing(_) => "owl:Thing",
+            Node::EquivalentClass(_) => "owl:equivalentClass",
+            Node::Union(_) => "owl:unionOf",
+            Node::DisjointUnion(_) => "owl:disjointUnionOf",
+            Node::DisjointClasses(_) => "owl:Class",
+            Node::Intersection(_) => "owl:intersectionOf",
+            Node::Complement(_) => "owl:complementOf",
+            Node::DeprecatedClass(_) => "owl:DeprecatedClass",
+            Node::AnonymousClass(_) => "owl:Class",
+            Node::Literal(_) => "rdfs:Literal",
+            Node::Datatype(_) => "rdfs:Datatype",
+            Node::RdfsClass(_) => "rdfs:Class",
+            Node::RdfsResource(_) => "rdfs:Resource",
+        }
+    }
+
+    /// How much the type of the node says about a class drawn under the
+    /// same id as other nodes. A plain class, thing or external class
+    /// gives way to a deprecated class, which gives way to the rest, such
+    /// as an equivalence or a set operator.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Node::Class(_) | Node::Thing(_) | Node::ExternalClass(_) => 0,
+            Node::DeprecatedClass(_) => 1,
+            _ => 2,
+        }
+    }
+
+    /// Additional WebVOWL `attributes` implied by the node variant.
+    pub fn vowl_attributes(&self) -> &'static [&'static str] {
+        match self {
+            Node::ExternalClass(_) => &["external"],
+            Node::DeprecatedClass(_) => &["deprecated"],
+            Node::AnonymousClass(_) => &["anonymous"],
+            Node::Union(_)
+            | Node::DisjointUnion(_)
+            | Node::Intersection(_)
+            | Node::Complement(_) => &["anonymous"],
+            Node::DisjointClasses(_) => &["anonymous", "disjoint"],
+            _ => &[],
+        }
+    }
+
+    /// The id the node is drawn under. Equivalent classes are drawn under
+    /// their first member.
+    pub fn id(&self) -> Option<&T> {
+        match self {
+            Node::Class(id)
+            | Node::ExternalClass(id)
+            | Node::Thing(id)
+            | Node::Union(id)
+            | Node::DisjointUnion(id)
+            | Node::DisjointClasses(id)
+            | Node::Intersection(id)
+            | Node::Complement(id)
+            | Node::DeprecatedClass(id)
+            | Node::AnonymousClass(id)
+            | Node::Literal(id)
+            | Node::Datatype(id)
+            | Node::RdfsClass(id)
+            | Node::RdfsResource(id) => Some(id),
+            Node::EquivalentClass(ids) => ids.first(),
+        }
+    }
+}
+
+impl<T> Edge<T> {
+    /// The WebVOWL `type` of this edge, or `None` if it is not drawn.
+    pub fn vowl_type(&self) -> Option<&'static str> {
+        match self {
+            Edge::ObjectProperty(..) => Some("owl:objectProperty"),
+            Edge::DatatypeProperty(..) => Some("owl:datatypeProperty"),
+            Edge::SubclassOf(..) => Some("rdfs:subClassOf"),
+            Edge::InverseProperty(..) => Some("owl:objectProperty"),
+            Edge::DisjointWith(..) => Some("owl:disjointWith"),
+            Edge::RdfProperty(..) => Some("rdf:Property"),
+            Edge::DeprecatedProperty(..) => Some("owl:DeprecatedProperty"),
+            Edge::ExternalProperty(..) => Some("owl:objectProperty"),
+            Edge::ValuesFrom(_, _, _, Restriction::SomeValuesFrom) => Some("owl:someValuesFrom"),
+            Edge::ValuesFrom(_, _, _, Restriction::AllValuesFrom) => Some("owl:allValuesFrom"),
+            Edge::ValuesFrom(_, _, _, Restriction::HasValue) => Some("owl:hasValue"),
+            Edge::ValuesFrom(_, _, _, Restriction::HasSelf) => Some("owl:hasSelf"),
+            // a cardinality restriction is drawn as its property, see
+            // `to_vowl` for restrictions on a data property
+            Edge::ValuesFrom(..) => Some("owl:objectProperty"),
+            Edge::SetOperator(..) | Edge::NoDraw => None,
+        }
+    }
+
+    /// Additional WebVOWL `attributes` implied by the edge variant.
+    pub fn vowl_attributes(&self) -> &'static [&'static str] {
+        match self {
+            Edge::ObjectProperty(..) | Edge::InverseProperty(..) => &["object"],
+            Edge::DatatypeProperty(..) => &["datatype"],
+            Edge::DeprecatedProperty(..) => &["deprecated"],
+            Edge::ExternalProperty(..) => &["external"],
+            Edge::SubclassOf(..) | Edge::DisjointWith(..) => &["anonymous"],
+            _ => &[],
+        }
+    }
+
+    /// `(domain, property, range)` of the edge. The property is `None` when
+    /// the edge has no entity of its own, e.g. `rdfs:subClassOf`.
+    pub fn endpoints(&self) -> Option<(&T, Option<&T>, &T)> {
+        match self {
+            Edge::ObjectProperty(from, prop, to)
+            | Edge::DatatypeProperty(from, prop, to)
+            | Edge::DeprecatedProperty(from, prop, to)
+            | Edge::ExternalProperty(from, prop, to)
+            | Edge::InverseProperty(from, prop, _, to)
+            | Edge::ValuesFrom(from, prop, to, _) => Some((from, Some(prop), to)),
+            Edge::SubclassOf(from, to)
+            | Edge::DisjointWith(from, to)
+            | Edge::RdfProperty(from, to)
+            | Edge::SetOperator(from, to) => Some((from, None, to)),
+            Edge::NoDraw => None,
+        }
+    }
+}
+
+/// Split an IRI into its namespace and local name.
+pub fn split_iri(iri: &str) -> (&str, &str) {
+    match iri.rfind(['#', '/', ':']) {
+        Some(i) if i + 1 < iri.len() => iri.split_at(i + 1),
+        _ => (iri, ""),
+    }
+}
+
+impl<A: ForIRI> HornedVOWLExtract<A> {
+    pub fn to_vowl(&self) -> VOWLJson {
+        let iris: HashMap<u32, &A> = self
+            .iricache()
+            .iter()
+            .map(|(iri, (id, _))| (*id, iri))
+            .collect();
+        let mut next_id = self.next_id();
+        let mut base_iris = BTreeSet::new();
+        let mut languages = BTreeSet::new();
+        let mut describe = |id: u32| {
+            let id = self.aliases().get(&id).copied().unwrap_or(id);
+            let iri = iris.get(&id).map(|iri| iri.to_string());
+            let Some(iri) = iri else {
+                return (None, None, BTreeMap::new(), BTreeMap::new());
+            };
+            let base = split_iri(&iri).0.to_string();
+            base_iris.insert(base.clone());
+            let (mut label, mut comment) = (BTreeMap::new(), BTreeMap::new());
+            if let Some(annotations) = self.annotations(&iri) {
+                label.extend(annotations.label.clone());
+                label.extend(annotations.pref_label.clone());
+                comment.extend(annotations.definition.clone());
+                comment.extend(annotations.comment.clone());
+            }
+            if label.is_empty() {
+                label.insert(IRI_BASED.to_string(), self.label(&iri));
+            }
+            languages.extend(label.keys().chain(comment.keys()).cloned());
+            (Some(iri), Some(base), label, comment)
+        };
+
+        let mut vowl = VOWLJson {
+            comment: "Created with horned-owl-serializer".to_string(),
+            namespace: self
+                .prefixes()
+                .iter()
+                .map(|(name, iri)| VOWLNamespace {
+                    name: name.clone(),
+                    iri: iri.clone(),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        // Without declared prefixes, the ontology IRI is the namespace.
+        if vowl.namespace.is_empty()
+            && let Some(iri) = self.ontology_iri()
+        {
+            vowl.namespace.push(VOWLNamespace {
+                name: String::new(),
+                iri: iri.to_string(),
+            });
+        }
+
+        // Nodes may be pushed more than once. Their attributes add up, and
+        // the type is that of the most specific variant, see
+        // `Node::precedence`.
+        let mut class_index: HashMap<u32, usize> = HashMap::new();
+        let mut precedence = vec![];
+        for node in self.nodes() {
+            let Some(&id) = node.id() else { continue };
+            let position = match class_index.get(&id) {
+                Some(&position) => position,
+                None => {
+                    let (iri, base_iri, label, comment) = describe(id);
+                    vowl.class.push(VOWLElement {
+                        id: id.to_string(),
+                        kind: String::new(),
+                    });
+                    vowl.class_attribute.push(VOWLClassAttribute {
+                        id: id.to_string(),
+                        iri,
+                        base_iri,
+                        label,
+                        comment,
+                        ..Default::default()
+                    });
+                    class_index.insert(id, vowl.class.len() - 1);
+                    precedence.push(None);
+                    vowl.class.len() - 1
+                }
+            };
+            let current = precedence[position];
+            if current.is_none_or(|current| {
+                node.precedence() > current || node.precedence() == current && current > 0
+            }) {
+                vowl.class[position].kind = node.vowl_type().to_string();
+                precedence[position] = Some(node.precedence());
+            }
+            let attribute = &mut vowl.class_attribute[position];
+            for a in node.vowl_attributes() {
+                if !attribute.attributes.iter().any(|b| b == a) {
+                    attribute.attributes.push(a.to_string());
+                }
+            }
+            if let Node::EquivalentClass(ids) = node {
+                attribute.equivalent = ids.iter().skip(1).map(u32::to_string).collect();
+            }
+        }
+
+        let mut seen_properties = BTreeSet::new();
+        let mut allocate = |property: Option<u32>| match property {
+            Some(property) if seen_properties.insert(property) => (property, describe(property)),
+            // A property drawn more than once gets a fresh id per edge.
+            Some(property) => {
+                next_id += 1;
+                (next_id - 1, describe(property))
+            }
+            None => {
+                next_id += 1;
+                (next_id - 1, (None, None, BTreeMap::new(), BTreeMap::new()))
+            }
+        };
+        for edge in self.edges() {
+            // Operands of set operators are attributes of the operator node.
+            if let Edge::SetOperator(from, to) = edge {
+                if let Some(&position) = class_index.get(from) {
+                    let attribute = &mut vowl.class_attribute[position];
+                    let operands = match vowl.class[position].kind.as_str() {
+                        "owl:unionOf" => &mut attribute.union,
+                        "owl:disjointUnionOf" => &mut attribute.disjoint_union,
+                        "owl:intersectionOf" => &mut attribute.intersection,
+                        "owl:complementOf" => &mut attribute.complement,
+                        _ => continue,
+                    };
+                    operands.push(to.to_string());
+                }
+                continue;
+            }
+            let (Some(kind), Some((&domain, property, &range))) =
+                (edge.vowl_type(), edge.endpoints())
+            else {
+                continue;
+            };
+            let (id, (iri, base_iri, label, comment)) = allocate(property.copied());
+            if let Edge::SubclassOf(sub, sup) = edge {
+                if let Some(&position) = class_index.get(sub) {
+                    vowl.class_attribute[position]
+                        .super_classes
+                        .push(sup.to_string());
+                }
+                if let Some(&position) = class_index.get(sup) {
+                    vowl.class_attribute[position]
+                        .sub_classes
+                        .push(sub.to_string());
+                }
+            }
+            let mut attributes: Vec<String> = edge
+                .vowl_attributes()
+                .iter()
+                .map(|a| a.to_string())
+                .collect();
+            let mut kind = kind;
+            let mut cardinalities = (None, None, None);
+            if let Edge::ValuesFrom(..)
+            | Edge::ExternalProperty(..)
+            | Edge::DeprecatedProperty(..) = edge
+            {
+                // Edges onto a literal or datatype use a data property.
+                let datatype = class_index.get(&range).is_some_and(|&position| {
+                    matches!(
+                        vowl.class[position].kind.as_str(),
+                        "rdfs:Literal" | "rdfs:Datatype"
+                    )
+                });
+                if datatype && kind == "owl:objectProperty" {
+                    kind = "owl:datatypeProperty";
+                }
+                attributes.push(if datatype { "datatype" } else { "object" }.to_string());
+            }
+            if let Edge::ValuesFrom(_, _, _, restriction) = edge {
+                cardinalities = match restriction {
+                    Restriction::MinCardinality(n) => (Some(n.to_string()), None, None),
+                    Restriction::MaxCardinality(n) => (None, Some(n.to_string()), None),
+                    Restriction::ExactCardinality(n) => (None, None, Some(n.to_string())),
+                    _ => (None, None, None),
+                };
+            }
+            vowl.property.push(VOWLElement {
+                id: id.to_string(),
+                kind: kind.to_string(),
+            });
+            vowl.property_attribute.push(VOWLPropertyAttribute {
+                id: id.to_string(),
+                iri,
+                base_iri,
+                label,
+                comment,
+                attributes,
+                domain: domain.to_string(),
+                range: range.to_string(),
+                min_cardinality: cardinalities.0,
+                max_cardinality: cardinalities.1,
+                cardinality: cardinalities.2,
+                ..Default::default()
+            });
+            // WebVOWL draws an inverse pair as two properties referring to
+            // each other, which it merges into one link.
+            if let Edge::InverseProperty(from, _, inverse, to) = edge {
+                let (inverse_id, (iri, base_iri, label, comment)) = allocate(Some(*inverse));
+                let attribute = vowl.property_attribute.last_mut().unwrap();
+                attribute.inverse = Some(inverse_id.to_string());
+                let attributes = attribute.attributes.clone();
+                vowl.property.push(VOWLElement {
+                    id: inverse_id.to_string(),
+                    kind: kind.to_string(),
+                });
+                vowl.property_attribute.push(VOWLPropertyAttribute {
+                    id: inverse_id.to_string(),
+                    iri,
+                    base_iri,
+                    label,
+                    comment,
+                    attributes,
+                    domain: to.to_string(),
+                    range: from.to_string(),
+                    inverse: Some(id.to_string()),
+                    ..Default::default()
+                });
+            }
+        }
+
+        vowl.header = VOWLHeader {
+            languages: languages.into_iter().collect(),
+            base_iris: base_iris.into_iter().collect(),
+            iri: self.ontology_iri().map(|iri| iri.to_string()),
+            version: self.version_iri().map(|iri| iri.to_string()),
+            ..Default::default()
+        };
+        vowl.metrics = VOWLMetrics {
+            class_count: count(&vowl.class, "owl:Class"),
+            object_property_count: count(&vowl.property, "owl:objectProperty"),
+            datatype_property_count: count(&vowl.property, "owl:datatypeProperty"),
+            individual_count: self.metrics().declaration_count("NamedIndividual"),
+            axiom_count: self.metrics().axiom_count(),
+            node_count: vowl.class.len(),
+            edge_count: vowl.property.len(),
+            ontology: self.metrics().clone(),
+        };
+        vowl
+    }
+
+    pub fn write_vowl<W: Write>(&self, writer: W) -> Result<(), HornedOxiError> {
+        serde_json::to_writer_pretty(writer, &self.to_vowl())?;
+        Ok(())
+    }
+}
+
+fn count(elements: &[VOWLElement], kind: &str) -> usize {
+    elements.iter().filter(|e| e.kind == kind).count()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use horned_owl::{
+        io::{ParserConfiguration, ofn},
+        model::RcStr,
+        ontology::set::SetOntology,
+    };
+    use std::{fs::File, io::BufReader};
+    use test_generator::test_resources;
+
+    fn extract(resource: &str) -> HornedVOWLExtract<RcStr> {
+        let mut reader = BufReader::new(File::open(resource).unwrap());
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut reader, ParserConfiguration::default()).unwrap();
+        ontology.into()
+    }
+
+    fn assert_connected(vowl: &VOWLJson) {
+        let ids: BTreeSet<_> = vowl.class.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids.len(), vowl.class.len(), "Duplicate class ids");
+        for property in &vowl.property_attribute {
+            assert!(ids.contains(property.domain.as_str()), "{:?}", property);
+            assert!(ids.contains(property.range.as_str()), "{:?}", property);
+        }
+    }
+
+    #[test_resources("data/owl-functional/*.ofn")]
+    fn test_vowl_ofn(resource: &str) {
+        assert_connected(&extract(resource).to_vowl());
+    }
+
+    #[test]
+    fn test_vowl_set_operators() {
+        let vowl = extract("data/owl-functional/or.ofn").to_vowl();
+        let union = vowl
+            .class
+            .iter()
+            .position(|c| c.kind == "owl:unionOf")
+            .unwrap();
+        assert_eq!(vowl.class_attribute[union].union.len(), 3);
+        assert_eq!(vowl.class_attribute[union].attributes, vec!["anonymous"]);
+    }
+
+    #[test]
+    fn test_vowl_disjoint_union() {
+        let vowl = extract("data/owl-functional/disjoint-union.ofn").to_vowl();
+        assert_connected(&vowl);
+        let union = vowl
+            .class
+            .iter()
+            .position(|c| c.kind == "owl:disjointUnionOf")
+            .unwrap();
+        assert_eq!(vowl.class_attribute[union].disjoint_union.len(), 2);
+        let equivalent = vowl
+            .class
+            .iter()
+            .position(|c| c.kind == "owl:equivalentClass")
+            .unwrap();
+        assert_eq!(
+            vowl.class_attribute[equivalent].equivalent,
+            vec![vowl.class[union].id.clone()]
+        );
+    }
+
+    #[test]
+    fn test_vowl_inverse_properties() {
+        let vowl = extract("data/owl-functional/inverse-properties.ofn").to_vowl();
+        assert_connected(&vowl);
+        assert_eq!(vowl.property.len(), 2);
+        let (first, second) = (&vowl.property_attribute[0], &vowl.property_attribute[1]);
+        assert_eq!(first.inverse.as_ref(), Some(&second.id));
+        assert_eq!(second.inverse.as_ref(), Some(&first.id));
+        assert_eq!(
+            (&first.domain, &first.range),
+            (&second.range, &second.domain)
+        );
+    }
+
+    #[test]
+    fn test_vowl_labels() {
+        let vowl = extract("data/owl-functional/comment.ofn").to_vowl();
+        let commented = vowl
+            .class_attribute
+            .iter()
+            .find(|c| !c.comment.is_empty())
+            .unwrap();
+        assert!(commented.comment.contains_key("en"));
+        assert!(commented.label.contains_key(IRI_BASED));
+        assert!(vowl.header.languages.contains(&"en".to_string()));
+    }
+
+    #[test]
+    fn test_vowl_deprecated_external_class() {
+        let ofn = "Prefix(o:=<http://www.example.com/iri#>)
+Prefix(foaf:=<http://xmlns.com/foaf/0.1/>)
+Prefix(owl:=<http://www.w3.org/2002/07/owl#>)
+Prefix(xsd:=<http://www.w3.org/2001/XMLSchema#>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(o:A))
+Declaration(Class(o:B))
+Declaration(Class(foaf:Person))
+AnnotationAssertion(owl:deprecated foaf:Person \"true\"^^xsd:boolean)
+AnnotationAssertion(owl:deprecated o:A \"true\"^^xsd:boolean)
+SubClassOf(o:B foaf:Person)
+EquivalentClasses(o:A o:B)
+)";
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut ofn.as_bytes(), ParserConfiguration::default()).unwrap();
+        let extract = HornedVOWLExtract::from(ontology);
+        let vowl = extract.to_vowl();
+        let class = |iri: &str| {
+            let id = extract.iricache()[iri].0.to_string();
+            let position = vowl.class.iter().position(|c| c.id == id).unwrap();
+            (&vowl.class[position].kind, &vowl.class_attribute[position])
+        };
+
+        let (kind, attribute) = class("http://xmlns.com/foaf/0.1/Person");
+        assert_eq!(kind, "owl:DeprecatedClass");
+        assert!(attribute.attributes.contains(&"external".to_string()));
+        assert!(attribute.attributes.contains(&"deprecated".to_string()));
+
+        let (kind, attribute) = class("http://www.example.com/iri#A");
+        assert_eq!(kind, "owl:equivalentClass");
+        assert!(attribute.attributes.contains(&"deprecated".to_string()));
+
+        assert_eq!(vowl.namespace.len(), 1);
+        assert_eq!(vowl.namespace[0].iri, "http://www.example.com/iri");
+    }
+
+    #[test]
+    fn test_vowl_deprecated_data_property() {
+        let ofn = "Prefix(o:=<http://www.example.com/iri#>)
+Prefix(owl:=<http://www.w3.org/2002/07/owl#>)
+Prefix(xsd:=<http://www.w3.org/2001/XMLSchema#>)
+Ontology(<http://www.example.com/iri>
+Declaration(Class(o:C))
+Declaration(DataProperty(o:d))
+AnnotationAssertion(owl:deprecated o:d \"true\"^^xsd:boolean)
+DataPropertyDomain(o:d o:C)
+)";
+        let (ontology, _): (SetOntology<RcStr>, _) =
+            ofn::reader::read(&mut ofn.as_bytes(), ParserConfiguration::default()).unwrap();
+        let vowl = HornedVOWLExtract::from(ontology).to_vowl();
+        assert_eq!(vowl.property[0].kind, "owl:DeprecatedProperty");
+        let attributes = &vowl.property_attribute[0].attributes;
+        assert!(attributes.contains(&"deprecated".to_string()));
+        assert!(attributes.contains(&"datatype".to_string()));
+        assert!(!attributes.contains(&"object".to_string()));
+    }
+
+    #[test]
+    fn test_vowl_restrictions() {
+        let vowl = extract("data/owl-functional/object-max-cardinality.ofn").to_vowl();
+        assert_connected(&vowl);
+        assert_eq!(vowl.property[0].kind, "owl:objectProperty");
+        assert_eq!(
+            vowl.property_attribute[0].max_cardinality.as_deref(),
+            Some("1")
+        );
+
+        let vowl = extract("data/owl-functional/data-exact-cardinality.ofn").to_vowl();
+        assert_eq!(vowl.property[0].kind, "owl:datatypeProperty");
+        assert_eq!(vowl.property_attribute[0].cardinality.as_deref(), Some("1"));
+
+        let vowl = extract("data/owl-functional/object-has-value.ofn").to_vowl();
+        assert_eq!(vowl.property[0].kind, "owl:hasValue");
+        assert!(
+            vowl.property_attribute[0]
+                .attributes
+                .contains(&"object".to_string())
+        );
+
+        let vowl = extract("data/owl-functional/data-has-value.ofn").to_vowl();
+        assert_eq!(vowl.property[0].kind, "owl:hasValue");
+        assert!(
+            vowl.property_attribute[0]
+                .attributes
+                .contains(&"datatype".to_string())
+        );
+
+        let vowl = extract("data/owl-functional/object-has-self.ofn").to_vowl();
+        assert_eq!(vowl.property[0].kind, "owl:hasSelf");
+
+        let vowl = extract("data/owl-functional/data-only.ofn").to_vowl();
+        assert_connected(&vowl);
+        assert_eq!(vowl.property[0].kind, "owl:allValuesFrom");
+        assert!(
+            vowl.property_attribute[0]
+                .attributes
+                .contains(&"datatype".to_string())
+        );
+    }
+
+    #[test]
+    fn test_vowl_subclass() {
+        let vowl = extract("data/owl-functional/subclass.ofn").to_vowl();
+        assert_connected(&vowl);
+        assert_eq!(
+            vowl.header.iri.as_deref(),
+            Some("http://www.example.com/iri")
+        );
+        assert_eq!(vowl.class.len(), 2);
+        assert_eq!(vowl.property.len(), 1);
+        assert_eq!(vowl.property[0].kind, "rdfs:subClassOf");
+
+        let sub = &vowl.property_attribute[0].domain;
+        let attribute = vowl.class_attribute.iter().find(|c| &c.id == sub).unwrap();
+        assert_eq!(
+            attribute.iri.as_deref(),
+            Some("http://www.example.com/iri#B")
+        );
+        assert_eq!(
+            attribute.base_iri.as_deref(),
+            Some("http://www.example.com/iri#")
+        );
+        assert_eq!(attribute.label[IRI_BASED], "B");
+        assert_eq!(
+            attribute.super_classes,
+            vec![vowl.property_attribute[0].range.clone()]
+        );
+    }
+
+    #[test]
+    fn test_vowl_object_property_assertion() {
+        let vowl = extract("data/owl-functional/object-property-assertion.ofn").to_vowl();
+        assert_connected(&vowl);
+        // One edge between the individuals, one for the declaration.
+        assert_eq!(vowl.property.len(), 2);
+        let ids: BTreeSet<_> = vowl.property.iter().map(|p| &p.id).collect();
+        assert_eq!(ids.len(), 2, "Property drawn twice needs fresh ids");
+        for (property, attribute) in vowl.property.iter().zip(&vowl.property_attribute) {
+            assert_eq!(property.kind, "owl:objectProperty");
+            assert_eq!(
+                attribute.iri.as_deref(),
+                Some("http://www.example.com/iri#r")
+            );
+        }
+    }
+
+    #[test]
+    fn test_vowl_json_schema() {
+        let mut buf = Vec::new();
+        extract("data/owl-functional/subclass.ofn")
+            .write_vowl(&mut buf)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+        for key in [
+            "header",
+            "namespace",
+            "metrics",
+            "class",
+            "classAttribute",
+            "property",
+            "propertyAttribute",
+        ] {
+            assert!(json.get(key).is_some(), "Missing {}", key);
+        }
+        assert_eq!(json["metrics"]["classCount"], 2);
+    }
+}
@@ -0,0 +1,8 @@
+pub mod catalog;
+pub mod rdf_xml;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod store;
+pub mod horned_oxi;
+#[cfg(test)]
+mod test_util;
\ No newline at end of file
@@ -1,37 +1,467 @@
-use std::path::Path;
-
-mod store;
-mod horned_oxi;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
 
+use clap::{Parser, Subcommand, ValueEnum};
 use horned_owl::model::RcStr;
-use oxigraph::store::Store;
-
-use crate::store::HornedOxiStore;
-
-pub fn main() {
-    //let session = Store::open("oxigraph.db").unwrap();
-    let session = Store::new().unwrap();
-    println!("Loaded {} quads", session.len().unwrap());
-    let path = Path::new("data/owl-rdf/owl1-compatible.owl");
-    let horned_oxi = HornedOxiStore::<RcStr>::new(session);
-    horned_oxi
-        .insert_file(&path, false)
-        .expect("Error inserting file");
-    println!("Loaded {} quads", horned_oxi.session.len().unwrap());
-    let ontology = horned_oxi
-        .get_ontology(format!("file:://{}", path.display().to_string()))
+use horned_owl_serializer::{
+    horned_oxi::{errors::HornedOxiError, horned_oxi::ExtractConfiguration},
+    store::{
+        GraphScope, HornedOxiStore, QueryResult, ResourceType, file_iri, is_archive, path_type,
+        uncompressed_path,
+    },
+};
+use oxigraph::{
+    io::{RdfFormat, RdfSerializer},
+    sparql::results::{QueryResultsFormat, QueryResultsSerializer},
+};
+use serde::Serialize;
+
+#[cfg(feature = "server")]
+use horned_owl_serializer::server::VowlServer;
+
+/// Load ontologies into a store and write them out as VOWL JSON or in
+/// another syntax.
+///
+/// Results are written as JSON unless a syntax is asked for. The exit code
+/// is 0 on success, 1 when anything failed and 2 for a bad command line.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    /// The directory of the on-disk store.
+    #[arg(long, global = true, default_value = "oxigraph.db")]
+    store: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Load files, and the ontology files under directories, each into its
+    /// own graph. Files whose content is already loaded are skipped.
+    Load {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Skip over syntax errors where the parser allows it.
+        #[arg(long)]
+        lenient: bool,
+    },
+    /// List the graphs of the store with the ontology each one declares.
+    List,
+    /// Write the VOWL JSON of a graph.
+    Convert {
+        graph: String,
+        /// Extract straight from the store with SPARQL.
+        #[arg(long)]
+        sparql: bool,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write a graph in another syntax.
+    Export {
+        graph: String,
+        #[arg(short, long, value_enum)]
+        format: Syntax,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Run the SPARQL query in a file. Solutions and booleans are written
+    /// in the results format, graphs as N-Triples.
+    Query {
+        file: PathBuf,
+        /// The graph to query, rather than the union of the graphs holding
+        /// ontologies.
+        #[arg(long)]
+        graph: Option<String>,
+        #[arg(short, long, value_enum, default_value_t = ResultsFormat::Json)]
+        format: ResultsFormat,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Serve the graphs to WebVOWL over HTTP on localhost until stopped.
+    #[cfg(feature = "server")]
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+    },
+}
+
+/// The syntaxes a graph can be exported to, by file extension.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Syntax {
+    Ofn,
+    Owx,
+    Rdf,
+    Owl,
+    Ttl,
+    Nt,
+    Nq,
+    Trig,
+    Jsonld,
+    N3,
+}
+
+impl From<Syntax> for ResourceType {
+    fn from(syntax: Syntax) -> Self {
+        match syntax {
+            Syntax::Ofn => ResourceType::OFN,
+            Syntax::Owx => ResourceType::OWX,
+            Syntax::Rdf => ResourceType::RDF,
+            Syntax::Owl => ResourceType::OWL,
+            Syntax::Ttl => ResourceType::TTL,
+            Syntax::Nt => ResourceType::NTriples,
+            Syntax::Nq => ResourceType::NQuads,
+            Syntax::Trig => ResourceType::TriG,
+            Syntax::Jsonld => ResourceType::JsonLd,
+            Syntax::N3 => ResourceType::N3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ResultsFormat {
+    Json,
+    Xml,
+    Csv,
+    Tsv,
+}
+
+impl From<ResultsFormat> for QueryResultsFormat {
+    fn from(format: ResultsFormat) -> Self {
+        match format {
+            ResultsFormat::Json => QueryResultsFormat::Json,
+            ResultsFormat::Xml => QueryResultsFormat::Xml,
+            ResultsFormat::Csv => QueryResultsFormat::Csv,
+            ResultsFormat::Tsv => QueryResultsFormat::Tsv,
+        }
+    }
+}
+
+/// What became of one file given to `load`.
+#[derive(Debug, Serialize)]
+struct Loaded {
+    path: PathBuf,
+    graph: Option<String>,
+    /// The syntax the file was read as, if it was loaded.
+    format: Option<String>,
+    status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Loaded,
+    Unchanged,
+    Failed,
+}
+
+pub fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let write = match cli.command {
+        Command::Load { .. } => true,
+        #[cfg(feature = "server")]
+        Command::Serve { .. } => true,
+        _ => false,
+    };
+    let result = open_store(&cli.store, write)
+        .and_then(|store| run(&store, cli.command, std::io::stdout().lock()));
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+fn open_store(path: &Path, write: bool) -> Result<HornedOxiStore<RcStr>, HornedOxiError> {
+    if write {
+        HornedOxiStore::open(path)
+    } else {
+        HornedOxiStore::open_read_only(path)
+    }
+}
+
+#[cfg(not(feature = "rocksdb"))]
+fn open_store(path: &Path, _write: bool) -> Result<HornedOxiStore<RcStr>, HornedOxiError> {
+    eprintln!(
+        "warning: built without the rocksdb feature, using an in-memory store instead of {}",
+        path.display()
+    );
+    Ok(HornedOxiStore::new(oxigraph::store::Store::new()?))
+}
+
+/// Run `command` against `store`, writing to `stdout` unless an output
+/// file is given. Returns whether everything succeeded.
+fn run(
+    store: &HornedOxiStore<RcStr>,
+    command: Command,
+    stdout: impl Write,
+) -> Result<bool, HornedOxiError> {
+    match command {
+        Command::Load { paths, lenient } => {
+            let mut files = Vec::new();
+            for path in &paths {
+                if path.is_dir() {
+                    ontology_files(path, &mut files)?;
+                } else {
+                    files.push(path.clone());
+                }
+            }
+            let loaded = files
+                .iter()
+                .flat_map(|path| load(store, path, lenient))
+                .collect::<Vec<_>>();
+            write_json(stdout, &loaded)?;
+            Ok(loaded.iter().all(|loaded| loaded.status != Status::Failed))
+        }
+        Command::List => {
+            write_json(stdout, &store.list_ontologies()?)?;
+            Ok(true)
+        }
+        Command::Convert {
+            graph,
+            sparql,
+            output,
+        } => {
+            let extract = if sparql {
+                store.extract_graph(&graph, ExtractConfiguration::default())?
+            } else {
+                store.extract_ontology(&graph, ExtractConfiguration::default())?
+            };
+            let mut out = output_to(output, stdout)?;
+            extract.write_vowl(&mut out)?;
+            out.flush()?;
+            Ok(true)
+        }
+        Command::Export {
+            graph,
+            format,
+            output,
+        } => {
+            let mut out = output_to(output, stdout)?;
+            store.export_ontology(&graph, format.into(), &mut out)?;
+            out.flush()?;
+            Ok(true)
+        }
+        Command::Query {
+            file,
+            graph,
+            format,
+            output,
+        } => {
+            let scope = match &graph {
+                Some(graph) => GraphScope::Graph(graph),
+                None => GraphScope::Union,
+            };
+            let result = store.query(&std::fs::read_to_string(file)?, scope)?;
+            let mut out = output_to(output, stdout)?;
+            let serializer = QueryResultsSerializer::from_format(format.into());
+            match result {
+                QueryResult::Solutions {
+                    variables,
+                    solutions,
+                } => {
+                    let mut serializer =
+                        serializer.serialize_solutions_to_writer(&mut out, variables)?;
+                    for solution in &solutions {
+                        serializer.serialize(solution)?;
+                    }
+                    serializer.finish()?;
+                }
+                QueryResult::Boolean(value) => {
+                    serializer.serialize_boolean_to_writer(&mut out, value)?;
+                }
+                QueryResult::Graph(triples) => {
+                    let mut serializer =
+                        RdfSerializer::from_format(RdfFormat::NTriples).for_writer(&mut out);
+                    for triple in &triples {
+                        serializer.serialize_triple(triple)?;
+                    }
+                    serializer.finish()?;
+                }
+            }
+            out.flush()?;
+            Ok(true)
+        }
+        #[cfg(feature = "server")]
+        Command::Serve { port } => {
+            let server = VowlServer::bind(store, port)?;
+            eprintln!("Serving on http://{}", server.local_addr());
+            server.run()?;
+            Ok(true)
+        }
+    }
+}
+
+/// Load one file or archive, reporting each graph it went into.
+fn load(store: &HornedOxiStore<RcStr>, path: &Path, lenient: bool) -> Vec<Loaded> {
+    let failed = |e: HornedOxiError| Loaded {
+        path: path.to_path_buf(),
+        graph: None,
+        format: None,
+        status: Status::Failed,
+        error: Some(e.to_string()),
+    };
+    if is_archive(path) {
+        return match store.insert_archive(path, lenient) {
+            Ok(graphs) => graphs
+                .into_iter()
+                .map(|(graph, resource_type)| Loaded {
+                    path: path.to_path_buf(),
+                    graph: Some(graph),
+                    format: Some(format!("{:?}", resource_type)),
+                    status: Status::Loaded,
+                    error: None,
+                })
+                .collect(),
+            Err(e) => vec![failed(e)],
+        };
+    }
+    let loaded = file_iri(path).and_then(|graph| {
+        let resource_type = store.insert_file_if_changed(path, lenient)?;
+        Ok((graph, resource_type))
+    });
+    vec![match loaded {
+        Ok((graph, resource_type)) => Loaded {
+            path: path.to_path_buf(),
+            graph: Some(graph),
+            status: match resource_type {
+                Some(_) => Status::Loaded,
+                None => Status::Unchanged,
+            },
+            format: resource_type.map(|resource_type| format!("{:?}", resource_type)),
+            error: None,
+        },
+        Err(e) => failed(e),
+    }]
+}
+
+/// The ontology files and archives under `dir`, recursively and in order.
+fn ontology_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), HornedOxiError> {
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            ontology_files(&path, files)?;
+        } else if is_archive(&path) || path_type(uncompressed_path(&path)).is_some() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn output_to<'a>(
+    output: Option<PathBuf>,
+    stdout: impl Write + 'a,
+) -> Result<Box<dyn Write + 'a>, HornedOxiError> {
+    Ok(match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(stdout),
+    })
+}
+
+fn write_json(mut out: impl Write, value: &impl Serialize) -> Result<(), HornedOxiError> {
+    serde_json::to_writer_pretty(&mut out, value)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use oxigraph::store::Store;
+
+    fn run_args(store: &HornedOxiStore<RcStr>, args: &[&str]) -> (bool, String) {
+        let cli = Cli::try_parse_from(
+            std::iter::once("horned-owl-serializer").chain(args.iter().copied()),
+        )
         .unwrap();
-    let horned_oxi_extract = horned_oxi.convert_ontology(ontology).unwrap();
-    println!("{:#?}", horned_oxi_extract);
-    /*
-    let results = horned_oxi.session.quads_for_pattern(
-        None,
-        None,
-        None,
-        None).collect::<Result<Vec<_>, _>>().unwrap();
-    for quad in results {
-        let triple: Triple = quad.into();
-        println!("{}", triple.to_string());
-    }
-    */
-}
\ No newline at end of file
+        let mut out = Vec::new();
+        let ok = run(store, cli.command, &mut out).unwrap();
+        (ok, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_load_and_list() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let (ok, out) = run_args(
+            &store,
+            &[
+                "load",
+                "data/owl-ttl/and.ttl",
+                "data/owl-functional/class.ofn",
+            ],
+        );
+        assert!(ok);
+        let loaded: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(loaded[0]["status"], "loaded");
+        assert_eq!(loaded[0]["format"], "TTL");
+        assert_eq!(loaded[1]["format"], "OFN");
+
+        let (ok, out) = run_args(&store, &["load", "data/owl-ttl/and.ttl"]);
+        assert!(ok);
+        let loaded: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(loaded[0]["status"], "unchanged");
+
+        let (ok, out) = run_args(&store, &["load", "data/owl-ttl/missing.ttl"]);
+        assert!(!ok);
+        let loaded: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(loaded[0]["status"], "failed");
+        assert!(loaded[0]["error"].is_string());
+
+        let (ok, out) = run_args(&store, &["list"]);
+        assert!(ok);
+        let graphs: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(graphs.as_array().unwrap().len(), 2);
+        assert!(
+            graphs
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|graph| graph["iri"] == "http://www.example.com/iri")
+        );
+    }
+
+    #[test]
+    fn test_convert_export_query() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let path = Path::new("data/owl-ttl/and.ttl");
+        store.insert_file(path, false).unwrap();
+        let graph = file_iri(path).unwrap();
+
+        for args in [vec!["convert", &graph], vec!["convert", "--sparql", &graph]] {
+            let (ok, out) = run_args(&store, &args);
+            assert!(ok);
+            let vowl: serde_json::Value = serde_json::from_str(&out).unwrap();
+            assert!(vowl["class"].as_array().is_some_and(|c| !c.is_empty()));
+        }
+
+        let (ok, out) = run_args(&store, &["export", &graph, "--format", "ofn"]);
+        assert!(ok);
+        assert!(out.contains("SubClassOf(:A ObjectIntersectionOf(:B :C :D))"));
+
+        let (ok, out) = run_args(
+            &store,
+            &["query", "data/sparql/named-classes.rq", "--graph", &graph],
+        );
+        assert!(ok);
+        let results: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(results["results"]["bindings"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_bad_command_line() {
+        let error = Cli::try_parse_from(["horned-owl-serializer", "export", "g"]).unwrap_err();
+        assert_eq!(error.exit_code(), 2);
+    }
+}
@@ -1,2 +0,0 @@
-pub mod store;
-pub mod horned_oxi;
\ No newline at end of file
@@ -0,0 +1,349 @@
+//! Feeding the quads of a graph to the horned-owl RDF reader, through
+//! RDF/XML.
+//!
+//! Handing Oxigraph triples to the reader directly is not done. Its only
+//! entry point below RDF/XML, `OntologyParser::new`, takes its own terms,
+//! and blank node terms cannot be made outside of horned-owl; it also
+//! takes every triple at once, so memory would not be bounded either way.
+//! Until that changes upstream, each quad is still written as RDF/XML, a
+//! standalone `rdf:Description` at a time as the parser reads, and parsed
+//! back. No document holding the whole graph is built, but the parser
+//! collects every triple before building the ontology. Blank nodes keep
+//! their identity through `rdf:nodeID`, and literals keep their lexical
+//! form, datatype and language exactly.
+//!
+//! Some graphs cannot be written this way, and fail to read instead:
+//!
+//! - predicates without a local name that is an XML name, such as
+//!   `http://example.com/p/` or `http://example.com/1`, or whose local name
+//!   RDF/XML reserves in the `rdf:` namespace, such as `rdf:li`;
+//! - literals with characters XML 1.0 does not allow, such as U+0000.
+use std::io::{self, BufRead, Read};
+
+use oxigraph::{
+    model::{NamedOrBlankNode, Quad, Term, vocab::xsd},
+    store::StorageError,
+};
+use quick_xml::escape::escape;
+
+use crate::horned_oxi::errors::{HornedOxiError, HornedOxiErrorKind};
+
+const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+const HEADER: &str = "<?xml version=\"1.0\"?>\n<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n";
+
+/// Property element names that RDF/XML reserves for its own syntax.
+const RESERVED: &[&str] = &[
+    "RDF",
+    "Description",
+    "ID",
+    "about",
+    "parseType",
+    "resource",
+    "nodeID",
+    "datatype",
+    "li",
+    "aboutEach",
+    "aboutEachPrefix",
+    "bagID",
+];
+
+/// An RDF/XML document written from an iterator of quads as it is read;
+/// the graph of each quad is ignored.
+///
+/// Reading never fails: the horned-owl parser treats errors in its input as
+/// fatal, so a failure ends the document early instead, and is returned by
+/// [`TripleReader::finish`].
+pub struct TripleReader<I> {
+    quads: I,
+    buf: Vec<u8>,
+    pos: usize,
+    state: State,
+    error: Option<HornedOxiError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Start,
+    Triples,
+    End,
+}
+
+impl<I: Iterator<Item = Result<Quad, StorageError>>> TripleReader<I> {
+    pub fn new(quads: I) -> Self {
+        Self {
+            quads,
+            buf: Vec::new(),
+            pos: 0,
+            state: State::Start,
+            error: None,
+        }
+    }
+
+    /// The error that ended the document early, if any.
+    pub fn finish(self) -> Result<(), HornedOxiError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Refill the buffer with the next part of the document.
+    fn next_chunk(&mut self) {
+        self.buf.clear();
+        self.pos = 0;
+        match self.state {
+            State::Start => {
+                self.buf.extend_from_slice(HEADER.as_bytes());
+                self.state = State::Triples;
+            }
+            State::Triples => {
+                let next = match self.quads.next() {
+                    Some(Ok(quad)) => match write_triple(&mut self.buf, &quad) {
+                        Ok(()) => return,
+                        Err(error) => Some(error),
+                    },
+                    Some(Err(error)) => Some(error.into()),
+                    None => None,
+                };
+                self.buf.clear();
+                self.error = next;
+                self.buf.extend_from_slice(b"</rdf:RDF>\n");
+                self.state = State::End;
+            }
+            State::End => {}
+        }
+    }
+}
+
+impl<I: Iterator<Item = Result<Quad, StorageError>>> Read for TripleReader<I> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<I: Iterator<Item = Result<Quad, StorageError>>> BufRead for TripleReader<I> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pos >= self.buf.len() && self.state != State::End {
+            self.next_chunk();
+        }
+        Ok(&self.buf[self.pos..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.pos = (self.pos + amount).min(self.buf.len());
+    }
+}
+
+/// Write one triple as an `rdf:Description`.
+fn write_triple(buf: &mut Vec<u8>, quad: &Quad) -> Result<(), HornedOxiError> {
+    let (namespace, local) = split_predicate(quad.predicate.as_str())?;
+    let subject = match &quad.subject {
+        NamedOrBlankNode::NamedNode(iri) => format!("rdf:about=\"{}\"", escape(iri.as_str())),
+        NamedOrBlankNode::BlankNode(bnode) => format!("rdf:nodeID=\"{}\"", node_id(bnode.as_str())),
+    };
+    let property = format!("p:{} xmlns:p=\"{}\"", local, escape(namespace));
+    let object = match &quad.object {
+        Term::NamedNode(iri) => {
+            format!("<{} rdf:resource=\"{}\"/>", property, escape(iri.as_str()))
+        }
+        Term::BlankNode(bnode) => {
+            format!("<{} rdf:nodeID=\"{}\"/>", property, node_id(bnode.as_str()))
+        }
+        Term::Literal(literal) => {
+            let annotation = match (literal.language(), literal.datatype()) {
+                (Some(lang), _) => format!(" xml:lang=\"{}\"", escape(lang)),
+                (None, xsd::STRING) => String::new(),
+                (None, datatype) => format!(" rdf:datatype=\"{}\"", escape(datatype.as_str())),
+            };
+            format!(
+                "<{}{}>{}</p:{}>",
+                property,
+                annotation,
+                text(literal.value())?,
+                local
+            )
+        }
+    };
+    buf.extend_from_slice(
+        format!(
+            "<rdf:Description {}>{}</rdf:Description>\n",
+            subject, object
+        )
+        .as_bytes(),
+    );
+    Ok(())
+}
+
+/// Split a predicate IRI into a namespace and a local name that is valid as
+/// an XML element name.
+fn split_predicate(iri: &str) -> Result<(&str, &str), HornedOxiError> {
+    let mut start = iri.len();
+    for (i, c) in iri.char_indices().rev() {
+        if !is_name_char(c) {
+            break;
+        }
+        if is_name_start_char(c) {
+            start = i;
+        }
+    }
+    let (namespace, local) = iri.split_at(start);
+    if local.is_empty() || (namespace == RDF && RESERVED.contains(&local)) {
+        return Err(HornedOxiErrorKind::InvalidInput(format!(
+            "Predicate {} cannot be read by the RDF/XML parser",
+            iri
+        ))
+        .into());
+    }
+    Ok((namespace, local))
+}
+
+/// An `rdf:nodeID` standing for a blank node label. Labels that are already
+/// XML names keep them; others are hex encoded under a different prefix so
+/// that distinct labels stay distinct.
+fn node_id(label: &str) -> String {
+    if label
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        format!("b{}", label)
+    } else {
+        let hex = label
+            .bytes()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        format!("x{}", hex)
+    }
+}
+
+/// A literal value as XML text. Whitespace only values are written as
+/// character references, as RDF/XML otherwise reads them as empty.
+fn text(value: &str) -> Result<String, HornedOxiError> {
+    if let Some(c) = value.chars().find(|c| !is_xml_char(*c)) {
+        return Err(HornedOxiErrorKind::InvalidInput(format!(
+            "Literal with character U+{:04X} cannot be read by the RDF/XML parser",
+            c as u32
+        ))
+        .into());
+    }
+    if value.chars().all(|c| c.is_ascii_whitespace()) {
+        return Ok(value
+            .chars()
+            .map(|c| format!("&#x{:X};", c as u32))
+            .collect());
+    }
+    Ok(escape(value).replace('\r', "&#xD;"))
+}
+
+fn is_xml_char(c: char) -> bool {
+    matches!(c,
+        '\t' | '\n' | '\r'
+        | '\u{20}'..='\u{D7FF}'
+        | '\u{E000}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{10FFFF}')
+}
+
+fn is_name_start_char(c: char) -> bool {
+    matches!(c,
+        'A'..='Z'
+        | '_'
+        | 'a'..='z'
+        | '\u{C0}'..='\u{D6}'
+        | '\u{D8}'..='\u{F6}'
+        | '\u{F8}'..='\u{2FF}'
+        | '\u{370}'..='\u{37D}'
+        | '\u{37F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start_char(c)
+        || matches!(c,
+            '-' | '.' | '0'..='9'
+            | '\u{B7}'
+            | '\u{0300}'..='\u{036F}'
+            | '\u{203F}'..='\u{2040}')
+}
+
+#[cfg(test)]
+mod test {
+    use oxigraph::{
+        io::{RdfFormat, RdfParser},
+        model::{
+            BlankNode, Graph, GraphNameRef, Literal, NamedNode, TripleRef,
+            dataset::CanonicalizationAlgorithm,
+        },
+    };
+
+    use super::*;
+
+    fn read_back(quads: &[Quad]) -> Result<Graph, HornedOxiError> {
+        let mut reader = TripleReader::new(quads.iter().cloned().map(Ok));
+        let mut document = Vec::new();
+        reader.read_to_end(&mut document)?;
+        reader.finish()?;
+        let mut graph = Graph::new();
+        for triple in RdfParser::from_format(RdfFormat::RdfXml).for_slice(&document) {
+            graph.insert(&triple.unwrap().into());
+        }
+        Ok(graph)
+    }
+
+    #[test]
+    fn test_triples_read_back() {
+        let s = NamedNode::new_unchecked("http://www.example.com/iri#s");
+        let p = NamedNode::new_unchecked("http://www.example.com/iri#p");
+        let odd = NamedNode::new_unchecked("http://www.example.com/vocab/1.2/p-1");
+        let objects: Vec<Term> = vec![
+            NamedNode::new_unchecked("http://www.example.com/iri?a=1&b='2'").into(),
+            BlankNode::new_unchecked("0a1b").into(),
+            BlankNode::new_unchecked("b.c").into(),
+            Literal::new_simple_literal("a < b & c\r\n").into(),
+            Literal::new_simple_literal("  ").into(),
+            Literal::new_language_tagged_literal_unchecked("chat", "fr").into(),
+            Literal::new_typed_literal("1", xsd::INTEGER).into(),
+        ];
+        let quads = objects
+            .into_iter()
+            .flat_map(|o| {
+                [
+                    Quad::new(s.clone(), p.clone(), o.clone(), GraphNameRef::DefaultGraph),
+                    Quad::new(
+                        BlankNode::new_unchecked("0a1b"),
+                        odd.clone(),
+                        o,
+                        GraphNameRef::DefaultGraph,
+                    ),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let mut expected = quads
+            .iter()
+            .map(|quad| TripleRef::from(quad.as_ref()))
+            .collect::<Graph>();
+        let mut actual = read_back(&quads).unwrap();
+        expected.canonicalize(CanonicalizationAlgorithm::Unstable);
+        actual.canonicalize(CanonicalizationAlgorithm::Unstable);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unreadable_predicate() {
+        let quad = Quad::new(
+            NamedNode::new_unchecked("http://www.example.com/iri#s"),
+            NamedNode::new_unchecked("http://www.example.com/iri/"),
+            Literal::new_simple_literal("x"),
+            GraphNameRef::DefaultGraph,
+        );
+        assert!(read_back(&[quad]).is_err());
+    }
+}
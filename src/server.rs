@@ -0,0 +1,314 @@
+//! A localhost HTTP service over a [`HornedOxiStore`], as a backend for
+//! WebVOWL.
+//!
+//! - `GET /graphs` lists the graphs of the store, as [`list_ontologies`]
+//!   does.
+//! - `PUT /graphs?graph=IRI&format=EXT` replaces the graph with the
+//!   ontology in the request body. `format` is a file extension such as
+//!   `ttl`; without it the syntax is detected. `POST` does the same.
+//! - `GET /vowl?graph=IRI` serves the VOWL JSON of the graph.
+//! - `DELETE /graphs?graph=IRI` removes the graph.
+//!
+//! Errors are answered with `{"error": "..."}`. VOWL JSON is served from
+//! the cache of the store, so a graph is only converted again once it has
+//! changed.
+//!
+//! [`list_ontologies`]: HornedOxiStore::list_ontologies
+use horned_owl::model::ForIRI;
+use serde::Serialize;
+use std::{
+    io::Cursor,
+    net::{Ipv4Addr, SocketAddr},
+};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{
+    horned_oxi::errors::{HornedOxiError, HornedOxiErrorKind},
+    store::{HornedOxiStore, extension_type},
+};
+
+/// Serves one store on the loopback interface, one request at a time.
+pub struct VowlServer<'a, A> {
+    store: &'a HornedOxiStore<A>,
+    http: tiny_http::Server,
+}
+
+#[derive(Serialize)]
+struct Uploaded<'a> {
+    graph: &'a str,
+    format: String,
+}
+
+impl<'a, A: ForIRI> VowlServer<'a, A> {
+    /// Listen on `port` of `127.0.0.1`; port 0 picks a free one, see
+    /// [`Self::local_addr`].
+    pub fn bind(store: &'a HornedOxiStore<A>, port: u16) -> Result<Self, HornedOxiError> {
+        let http =
+            tiny_http::Server::http((Ipv4Addr::LOCALHOST, port)).map_err(std::io::Error::other)?;
+        Ok(Self { store, http })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.http
+            .server_addr()
+            .to_ip()
+            .expect("the server listens on a TCP address")
+    }
+
+    /// Answer requests until accepting one fails. Failing to answer one,
+    /// as when the client went away, is reported on stderr.
+    pub fn run(&self) -> Result<(), HornedOxiError> {
+        loop {
+            self.handle_next()?;
+        }
+    }
+
+    /// Wait for the next request and answer it. Only waiting can fail;
+    /// failing to write the answer is reported on stderr.
+    pub fn handle_next(&self) -> Result<(), HornedOxiError> {
+        let mut request = self.http.recv()?;
+        let target = format!("{} {}", request.method(), request.url());
+        let response = self.respond(&mut request).unwrap_or_else(|e| {
+            let status = match e.kind() {
+                HornedOxiErrorKind::OxigraphError(_)
+                | HornedOxiErrorKind::IOError(_)
+                | HornedOxiErrorKind::JsonError(_) => 500,
+                _ => 400,
+            };
+            error(status, &e.to_string())
+        });
+        if let Err(e) = request.respond(response) {
+            eprintln!("error: answering {}: {}", target, e);
+        }
+        Ok(())
+    }
+
+    fn respond(&self, request: &mut Request) -> Result<Response<Cursor<Vec<u8>>>, HornedOxiError> {
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let graph = query_param(query, "graph");
+        match (path, request.method(), graph) {
+            ("/graphs", Method::Get, _) => json(200, &self.store.list_ontologies()?),
+            ("/graphs" | "/vowl", Method::Put | Method::Post | Method::Delete, None)
+            | ("/vowl", Method::Get, None) => Ok(error(400, "The graph parameter is missing")),
+            ("/graphs", Method::Put | Method::Post, Some(graph)) => {
+                let hint = match query_param(query, "format") {
+                    Some(format) => match extension_type(&format) {
+                        Some(resource_type) => Some(resource_type),
+                        None => {
+                            return Ok(error(400, &format!("Unknown format {}", format)));
+                        }
+                    },
+                    None => None,
+                };
+                let lenient = query_param(query, "lenient").is_some_and(|v| v == "true");
+                let mut bytes = Vec::new();
+                request.as_reader().read_to_end(&mut bytes)?;
+                let resource_type = self
+                    .store
+                    .replace_graph_with_bytes(bytes, hint, &graph, lenient)?;
+                json(
+                    201,
+                    &Uploaded {
+                        graph: &graph,
+                        format: format!("{:?}", resource_type),
+                    },
+                )
+            }
+            ("/graphs", Method::Delete, Some(graph)) => {
+                if self.store.remove_graph(&graph)? {
+                    Ok(Response::from_data(Vec::new()).with_status_code(204))
+                } else {
+                    Ok(not_found(&graph))
+                }
+            }
+            ("/vowl", Method::Get, Some(graph)) => {
+                if !self.store.contains_graph(&graph)? {
+                    return Ok(not_found(&graph));
+                }
+                let vowl = self.store.vowl_json(&graph)?;
+                Ok(Response::from_data(vowl.as_bytes().to_vec()).with_header(json_header()))
+            }
+            ("/graphs" | "/vowl", _, _) => Ok(error(405, "Method not allowed")),
+            _ => Ok(error(404, &format!("No such resource {}", path))),
+        }
+    }
+}
+
+fn json(status: u16, value: &impl Serialize) -> Result<Response<Cursor<Vec<u8>>>, HornedOxiError> {
+    Ok(Response::from_data(serde_json::to_vec(value)?)
+        .with_status_code(status)
+        .with_header(json_header()))
+}
+
+fn error(status: u16, message: &str) -> Response<Cursor<Vec<u8>>> {
+    let body = serde_json::json!({ "error": message }).to_string();
+    Response::from_string(body)
+        .with_status_code(status)
+        .with_header(json_header())
+}
+
+fn not_found(graph: &str) -> Response<Cursor<Vec<u8>>> {
+    error(404, &format!("No such graph {}", graph))
+}
+
+fn json_header() -> Header {
+    Header::from_bytes("Content-Type", "application/json").expect("the header is valid")
+}
+
+/// The percent-decoded value of `name` in the query string `query`.
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('=').or(Some((pair, ""))))
+        .find(|(key, _)| percent_decode(key) == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+fn percent_decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (byte, hex) {
+            (b'%', Some(decoded)) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            (b'+', _) => {
+                bytes.push(b' ');
+                rest = tail;
+            }
+            _ => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use horned_owl::model::RcStr;
+    use oxigraph::store::Store;
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        sync::mpsc,
+        thread,
+    };
+
+    /// Send one HTTP/1.0 request, so that the response is neither chunked
+    /// nor kept alive, and return its status and body.
+    fn request(addr: SocketAddr, method: &str, target: &str, body: &[u8]) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.0\r\nContent-Length: {}\r\n\r\n",
+            method,
+            target,
+            body.len()
+        )
+        .unwrap();
+        stream.write_all(body).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        (status, body.to_string())
+    }
+
+    /// Serve a fresh store on another thread for `requests` requests.
+    fn serve(requests: usize) -> (SocketAddr, thread::JoinHandle<()>) {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+            let server = VowlServer::bind(&store, 0).unwrap();
+            sender.send(server.local_addr()).unwrap();
+            for _ in 0..requests {
+                server.handle_next().unwrap();
+            }
+        });
+        (receiver.recv().unwrap(), handle)
+    }
+
+    #[test]
+    fn test_serve_vowl() {
+        let (addr, handle) = serve(10);
+        let graph = "graph=http%3A%2F%2Fwww.example.com%2Fupload";
+
+        let and = std::fs::read("data/owl-ttl/and.ttl").unwrap();
+        let (status, body) = request(addr, "PUT", &format!("/graphs?{}&format=ttl", graph), &and);
+        assert_eq!(status, 201, "{}", body);
+        let uploaded: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(uploaded["format"], "TTL");
+
+        let (status, body) = request(addr, "GET", "/graphs", &[]);
+        assert_eq!(status, 200);
+        let graphs: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(graphs[0]["graph"], "http://www.example.com/upload");
+
+        let (status, first) = request(addr, "GET", &format!("/vowl?{}", graph), &[]);
+        assert_eq!(status, 200);
+        assert!(first.contains("http://www.example.com/iri#D"));
+        let (_, second) = request(addr, "GET", &format!("/vowl?{}", graph), &[]);
+        assert_eq!(first, second);
+
+        // Without a format the syntax is detected, and the graph replaced.
+        let class = std::fs::read("data/owl-functional/class.ofn").unwrap();
+        let (status, body) = request(addr, "POST", &format!("/graphs?{}", graph), &class);
+        assert_eq!(status, 201, "{}", body);
+        let (_, body) = request(addr, "GET", &format!("/vowl?{}", graph), &[]);
+        assert!(!body.contains("http://www.example.com/iri#D"));
+        assert!(body.contains("http://www.example.com/iri#C"));
+
+        let (status, _) = request(addr, "DELETE", &format!("/graphs?{}", graph), &[]);
+        assert_eq!(status, 204);
+        let (status, body) = request(addr, "GET", &format!("/vowl?{}", graph), &[]);
+        assert_eq!(status, 404);
+        assert!(body.contains("No such graph"));
+
+        let (status, _) = request(addr, "PUT", "/graphs?format=ttl", &and);
+        assert_eq!(status, 400);
+        let (status, _) = request(
+            addr,
+            "PUT",
+            &format!("/graphs?{}&format=ttl", graph),
+            b"not turtle",
+        );
+        assert_eq!(status, 400);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_serve_after_disconnect() {
+        let (addr, handle) = serve(3);
+        for _ in 0..2 {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET /graphs HTTP/1.0\r\n\r\n").unwrap();
+            stream.shutdown(std::net::Shutdown::Both).unwrap();
+        }
+        let (status, body) = request(addr, "GET", "/graphs", &[]);
+        assert_eq!(status, 200);
+        assert_eq!(body, "[]");
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_query_param() {
+        let query = "graph=http%3A%2F%2Fexample.com%2Fa%20b&format=ttl&lenient";
+        assert_eq!(
+            query_param(query, "graph").as_deref(),
+            Some("http://example.com/a b")
+        );
+        assert_eq!(query_param(query, "format").as_deref(), Some("ttl"));
+        assert_eq!(query_param(query, "lenient").as_deref(), Some(""));
+        assert_eq!(query_param(query, "missing"), None);
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+}
@@ -1,80 +1,1067 @@
 use horned_owl::{
-    io::{rdf::reader::ConcreteRDFOntology, *},
-    model::{ForIRI, RcAnnotatedComponent, RcStr},
+    curie::PrefixMapping,
+    io::{
+        rdf::reader::{self, ConcreteRDFOntology, IncompleteParse},
+        *,
+    },
+    model::{
+        Annotation, Atom, ClassExpression, DataRange, ForIRI, ObjectPropertyExpression,
+        RcAnnotatedComponent, RcStr,
+    },
     ontology::{component_mapped::RcComponentMappedOntology, set::SetOntology},
+    vocab::OWL,
 };
 use oxigraph::{
-    io::{JsonLdProfileSet, RdfFormat, RdfParser, RdfSerializer},
-    model::{NamedNode, NamedNodeRef},
-    store::Store,
+    io::{JsonLdProfileSet, RdfFormat, RdfParser, RdfSerializer, SliceQuadParser},
+    model::{
+        BlankNode, Literal, NamedNode, NamedNodeRef, NamedOrBlankNode, Quad, QuadRef, Term, Triple,
+        TripleRef, vocab::rdf::TYPE,
+    },
+    sparql::{QueryResults, QuerySolution, SparqlEvaluator, Variable},
+    store::{BulkLoader, StorageError, Store, Transaction},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use spargebra::{
+    GraphUpdateOperation, SparqlParser, Update,
+    algebra::{Expression, GraphPattern, GraphTarget, QueryDataset},
+    term::{GraphName, GraphNamePattern},
 };
 use std::{
-    fs::File,
-    io::{BufReader, Cursor},
+    collections::{BTreeSet, HashMap},
+    io::{BufReader, Cursor, Read, Write},
     marker::PhantomData,
-    path::Path,
+    path::{Component, Path},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
 };
 
-use crate::horned_oxi::{
-    errors::{HornedOxiError, HornedOxiErrorKind},
-    horned_oxi::HornedVOWLExtract,
+use crate::{
+    catalog::Catalog,
+    horned_oxi::{
+        errors::{HornedOxiError, HornedOxiErrorKind},
+        horned_oxi::{ExtractConfiguration, HornedVOWLExtract},
+        sparql::SparqlReader,
+    },
+    rdf_xml::TripleReader,
 };
 
+/// The outcome of loading an ontology together with its imports closure.
+#[derive(Debug, Default)]
+pub struct ImportClosure {
+    /// Ontology IRI and graph name of each loaded ontology, root first.
+    pub graphs: Vec<(String, String)>,
+    /// Imports that could not be resolved to a local file.
+    pub missing: Vec<String>,
+    /// Imports that were resolved but could not be loaded.
+    pub failed: Vec<(String, HornedOxiError)>,
+    /// Chains of ontology IRIs ending in an import of an ontology that
+    /// was still being loaded.
+    pub cycles: Vec<Vec<String>>,
+}
+
+/// An ontology whose imports [`HornedOxiStore::insert_file_with_imports`]
+/// is still loading, with the names an import may refer to it by.
+struct Importing {
+    iri: String,
+    graph: String,
+    names: Vec<String>,
+}
+
+/// What the horned-owl RDF reader could not turn into axioms when reading
+/// a graph. The reader keeps the labels of blank nodes private, so blank
+/// nodes are fresh, though the same node is the same throughout.
+#[derive(Debug)]
+pub struct ParseDiagnostics<A: ForIRI> {
+    /// How many triples outside of blank node groups were not read into
+    /// any axiom. The reader keeps their terms private, so only their
+    /// number is known.
+    pub simple_triples: usize,
+    /// The triples on each blank node that was not read into any axiom.
+    pub blank_nodes: Vec<Vec<Triple>>,
+    /// RDF lists that were not read into any axiom.
+    pub lists: Vec<Vec<Term>>,
+    /// Expressions that were read but not used in any axiom.
+    pub class_expressions: Vec<ClassExpression<A>>,
+    pub object_property_expressions: Vec<ObjectPropertyExpression<A>>,
+    pub data_ranges: Vec<DataRange<A>>,
+    /// SWRL atoms that were not used in any rule.
+    pub atoms: Vec<Atom<A>>,
+    /// Annotations of reified triples that match no axiom.
+    pub annotations: Vec<(Triple, BTreeSet<Annotation<A>>)>,
+}
+
+impl<A: ForIRI> ParseDiagnostics<A> {
+    /// Whether the whole graph was read.
+    pub fn is_empty(&self) -> bool {
+        self.simple_triples == 0
+            && self.blank_nodes.is_empty()
+            && self.lists.is_empty()
+            && self.class_expressions.is_empty()
+            && self.object_property_expressions.is_empty()
+            && self.data_ranges.is_empty()
+            && self.atoms.is_empty()
+            && self.annotations.is_empty()
+    }
+}
+
+/// The graph in which the store keeps its own bookkeeping, such as content
+/// hashes. It is not listed as an ontology.
+pub const METADATA_GRAPH: NamedNodeRef<'static> =
+    NamedNodeRef::new_unchecked("urn:x-horned-oxi:metadata");
+/// Relates a graph to the SHA-256 of the file it was loaded from.
+pub const CONTENT_HASH: NamedNodeRef<'static> =
+    NamedNodeRef::new_unchecked("urn:x-horned-oxi:contentHash");
+/// Relates a graph to a prefix declared by the file it was loaded from,
+/// written as `name=IRI`.
+pub const PREFIX: NamedNodeRef<'static> = NamedNodeRef::new_unchecked("urn:x-horned-oxi:prefix");
+
+/// The graphs a SPARQL query or update is evaluated against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphScope<'a> {
+    /// One graph, which is both the default graph and the only named graph.
+    Graph(&'a str),
+    /// Every graph holding an ontology, merged into the default graph and
+    /// each available as a named graph.
+    Union,
+}
+
+/// The result of [`HornedOxiStore::query`].
+#[derive(Debug, PartialEq)]
+pub enum QueryResult {
+    /// The solutions of a SELECT query, with the variables it projects.
+    Solutions {
+        variables: Vec<Variable>,
+        solutions: Vec<QuerySolution>,
+    },
+    /// The answer to an ASK query.
+    Boolean(bool),
+    /// The triples built by a CONSTRUCT or DESCRIBE query.
+    Graph(Vec<Triple>),
+}
+
+/// An ontology loaded into the store, and the graph holding it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoadedOntology {
+    pub graph: String,
+    pub iri: Option<String>,
+    pub version_iri: Option<String>,
+}
+
 pub struct HornedOxiStore<A> {
     pub session: Store,
+    extracts: Mutex<Extracts>,
     phantom: PhantomData<A>,
 }
+
+/// VOWL JSON by graph name, dropped when the graph is changed through the
+/// store.
+#[derive(Default)]
+struct Extracts {
+    json: HashMap<String, Arc<str>>,
+    /// How often each graph was changed, so that JSON converted from a
+    /// graph that changed in the meantime is not kept.
+    generations: HashMap<String, u64>,
+}
+
+impl Extracts {
+    fn generation(&self, graph: &str) -> u64 {
+        self.generations.get(graph).copied().unwrap_or(0)
+    }
+}
 impl<A: ForIRI> HornedOxiStore<A> {
     pub fn new(session: Store) -> Self {
         Self {
             session,
+            extracts: Mutex::default(),
             phantom: PhantomData,
         }
     }
 
-    // TTL format -> (oxittl) RDF XML quads -> (horned_owl) Normalize OWL/RDF -> Quads -> Insert into Oxigraph
-    pub fn insert_file(&self, fs: &Path, lenient: bool) -> Result<(), HornedOxiError> {
-        let parser = parser_from_format(fs, lenient)?;
-        let mut b_loader = self.session.bulk_loader();
-        b_loader.parallel_load_from_slice(parser.parser, parser.input.as_slice())?;
-        b_loader.commit()?;
+    /// Open the on-disk store at `path`, creating it if it does not exist.
+    /// Ontologies loaded in earlier sessions are still present; see
+    /// [`Self::list_ontologies`].
+    #[cfg(feature = "rocksdb")]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, HornedOxiError> {
+        Ok(Self::new(Store::open(path)?))
+    }
+
+    /// Open the on-disk store at `path` without allowing writes.
+    #[cfg(feature = "rocksdb")]
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, HornedOxiError> {
+        Ok(Self::new(Store::open_read_only(path)?))
+    }
 
+    /// Write a backup of an on-disk store into `target`, which must not
+    /// exist yet. The backup is itself a store that can be opened or passed
+    /// to [`Self::restore`].
+    #[cfg(feature = "rocksdb")]
+    pub fn backup(&self, target: impl AsRef<Path>) -> Result<(), HornedOxiError> {
+        self.session.flush()?;
+        self.session.backup(target)?;
         Ok(())
     }
 
-    pub fn get_ontology(&self, ontology_id: String) -> Result<SetOntology<RcStr>, HornedOxiError> {
-        let quads = self
+    /// Copy the backup at `backup` to `path` and open it there. Fails if
+    /// `path` already exists, rather than overwriting a store.
+    #[cfg(feature = "rocksdb")]
+    pub fn restore(
+        backup: impl AsRef<Path>,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, HornedOxiError> {
+        let path = path.as_ref();
+        if path.exists() {
+            return Err(HornedOxiErrorKind::InvalidInput(format!(
+                "Cannot restore into {}, which already exists",
+                path.display()
+            ))
+            .into());
+        }
+        copy_dir(backup.as_ref(), path)?;
+        Self::open(path)
+    }
+
+    /// Whether a graph named `graph` is present in the store.
+    pub fn contains_graph(&self, graph: &str) -> Result<bool, HornedOxiError> {
+        Ok(self
             .session
-            .quads_for_pattern(
-                None,
-                None,
-                None,
-                Some(NamedNodeRef::new(&ontology_id)?.into()),
-            )
+            .contains_named_graph(NamedNodeRef::new(graph)?)?)
+    }
+
+    fn require_graph(&self, graph: &str) -> Result<(), HornedOxiError> {
+        if !self.contains_graph(graph)? {
+            return Err(
+                HornedOxiErrorKind::InvalidInput(format!("No graph named {}", graph)).into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// The SHA-256 of the file `graph` was loaded from by
+    /// [`Self::insert_file_if_changed`], as lowercase hex.
+    pub fn content_hash(&self, graph: &str) -> Result<Option<String>, HornedOxiError> {
+        let graph = NamedNodeRef::new(graph)?;
+        for quad in self.session.quads_for_pattern(
+            Some(graph.into()),
+            Some(CONTENT_HASH),
+            None,
+            Some(METADATA_GRAPH.into()),
+        ) {
+            if let Term::Literal(hash) = quad?.object {
+                return Ok(Some(hash.value().to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The prefixes declared by the files loaded into `graph`, by name.
+    pub fn prefixes(&self, graph: &str) -> Result<Vec<(String, String)>, HornedOxiError> {
+        let graph = NamedNodeRef::new(graph)?;
+        let mut prefixes = Vec::new();
+        for quad in self.session.quads_for_pattern(
+            Some(graph.into()),
+            Some(PREFIX),
+            None,
+            Some(METADATA_GRAPH.into()),
+        ) {
+            if let Term::Literal(prefix) = quad?.object
+                && let Some((name, iri)) = prefix.value().split_once('=')
+            {
+                prefixes.push((name.to_string(), iri.to_string()));
+            }
+        }
+        prefixes.sort();
+        Ok(prefixes)
+    }
+
+    /// Load `fs` into its graph unless the graph already holds the same
+    /// file content, judged by its SHA-256. A changed file replaces the old
+    /// content of the graph in a single transaction. Returns the type the
+    /// file was read as, or `None` if it was unchanged and skipped.
+    pub fn insert_file_if_changed(
+        &self,
+        fs: &Path,
+        lenient: bool,
+    ) -> Result<Option<ResourceType>, HornedOxiError> {
+        let graph = file_iri(fs)?;
+        // The content is read once, so that the hash is of what is loaded.
+        let bytes = std::fs::read(fs)?;
+        let hash = hex(&Sha256::digest(&bytes));
+        if self.contains_graph(&graph)? && self.content_hash(&graph)?.as_ref() == Some(&hash) {
+            return Ok(None);
+        }
+        let graph = NamedNodeRef::new(&graph)?;
+        let parser = parser_for_file_content(fs, bytes, graph, None, lenient)?;
+        let hash = Literal::new_simple_literal(hash);
+        let resource_type = self.load_with(parser, |transaction| {
+            transaction.remove_named_graph(graph)?;
+            let old = transaction
+                .quads_for_pattern(Some(graph.into()), None, None, Some(METADATA_GRAPH.into()))
+                .collect::<Result<Vec<_>, _>>()?;
+            for quad in &old {
+                if quad.predicate == CONTENT_HASH || quad.predicate == PREFIX {
+                    transaction.remove(quad);
+                }
+            }
+            transaction.insert(QuadRef::new(graph, CONTENT_HASH, &hash, METADATA_GRAPH));
+            Ok(())
+        })?;
+        Ok(Some(resource_type))
+    }
+
+    // TTL format -> (oxittl) RDF XML quads -> (horned_owl) Normalize OWL/RDF -> Quads -> Insert into Oxigraph
+    pub fn insert_file(&self, fs: &Path, lenient: bool) -> Result<ResourceType, HornedOxiError> {
+        self.insert_file_into(fs, &file_iri(fs)?, None, lenient)
+    }
+
+    /// Insert `fs` into the graph named `graph` rather than the one derived
+    /// from its path, resolving relative IRIs against `base_iri` if given.
+    /// Returns the type the file was read as, which is detected from its
+    /// content when the extension is missing or wrong.
+    pub fn insert_file_into(
+        &self,
+        fs: &Path,
+        graph: &str,
+        base_iri: Option<&str>,
+        lenient: bool,
+    ) -> Result<ResourceType, HornedOxiError> {
+        self.load(parser_for_graph(
+            fs,
+            NamedNodeRef::new(graph)?,
+            base_iri,
+            lenient,
+        )?)
+    }
+
+    /// Insert an ontology read from `reader` into `graph`.
+    pub fn insert_reader(
+        &self,
+        mut reader: impl Read,
+        resource_type: ResourceType,
+        graph: &str,
+        base_iri: Option<&str>,
+        lenient: bool,
+    ) -> Result<ResourceType, HornedOxiError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        self.load(parser_for_input(
+            ParserInput::Buffer(Cursor::new(bytes)),
+            resource_type,
+            NamedNodeRef::new(graph)?,
+            base_iri,
+            lenient,
+        )?)
+    }
+
+    /// Insert an ontology held in memory into `graph`.
+    pub fn insert_bytes(
+        &self,
+        bytes: &[u8],
+        resource_type: ResourceType,
+        graph: &str,
+        base_iri: Option<&str>,
+        lenient: bool,
+    ) -> Result<ResourceType, HornedOxiError> {
+        self.insert_reader(bytes, resource_type, graph, base_iri, lenient)
+    }
+
+    /// Insert every ontology in the zip archive `fs`, each into its own
+    /// graph named by [`archive_entry_iri`]. Entries that are not ontologies,
+    /// judging by their extension, are skipped. Returns the graph name and
+    /// detected type of each loaded entry, in archive order.
+    ///
+    /// Entries larger than [`MAX_ARCHIVE_ENTRY_SIZE`] once decompressed are
+    /// refused. All entries are loaded in a single transaction, so that if
+    /// any of them fails the store is left as it was.
+    pub fn insert_archive(
+        &self,
+        fs: &Path,
+        lenient: bool,
+    ) -> Result<Vec<(String, ResourceType)>, HornedOxiError> {
+        let mut archive = zip::ZipArchive::new(BufReader::new(std::fs::File::open(fs)?))?;
+        let mut transaction = self.session.start_transaction()?;
+        let mut loaded = Vec::new();
+        for index in 0..archive.len() {
+            let entry = archive.by_index(index)?;
+            let Some(name) = entry.enclosed_name().filter(|_| entry.is_file()) else {
+                continue;
+            };
+            let Some(hint) = path_type(&name) else {
+                continue;
+            };
+            let bytes = read_at_most(entry, MAX_ARCHIVE_ENTRY_SIZE)?;
+            let graph = archive_entry_iri(fs, &name)?;
+            let parser = parser_for_detected_input(
+                ParserInput::Buffer(Cursor::new(decompress_at_most(
+                    bytes,
+                    &name,
+                    MAX_ARCHIVE_ENTRY_SIZE,
+                )?)),
+                Some(hint),
+                NamedNodeRef::new(&graph)?,
+                Some(&graph),
+                lenient,
+            )?;
+            let (parser, (quads, prefixes)) = with_fallback(parser, PreparedParser::read)?;
+            transaction.extend(&quads);
+            record_prefixes(&mut transaction, parser.graph.as_ref(), &prefixes)?;
+            loaded.push((graph, parser.resource_type));
+        }
+        transaction.commit()?;
+        for (graph, _) in &loaded {
+            self.invalidate(graph);
+        }
+        Ok(loaded)
+    }
+
+    /// Bulk load a prepared parser into its graph. Input that does not
+    /// read as the type it was prepared for is tried as the other
+    /// candidate types in turn, failing with the error of the first;
+    /// nothing of an attempt that fails is kept.
+    fn load(&self, parser: PreparedParser) -> Result<ResourceType, HornedOxiError> {
+        let (parser, prefixes) = with_fallback(parser, |parser| {
+            let mut loader = self.session.bulk_loader();
+            let prefixes = parser.bulk_load_into(&mut loader)?;
+            loader.commit()?;
+            Ok(prefixes)
+        })?;
+        let mut transaction = self.session.start_transaction()?;
+        record_prefixes(&mut transaction, parser.graph.as_ref(), &prefixes)?;
+        transaction.commit()?;
+        self.invalidate(parser.graph.as_str());
+        Ok(parser.resource_type)
+    }
+
+    /// Load `parser` into its graph in a single transaction, after
+    /// `prepare` has run in it. Input that does not read as the type it
+    /// was prepared for is tried as the other candidate types in turn,
+    /// each in a fresh transaction, failing with the error of the first.
+    fn load_with(
+        &self,
+        parser: PreparedParser,
+        prepare: impl Fn(&mut Transaction<'_>) -> Result<(), HornedOxiError>,
+    ) -> Result<ResourceType, HornedOxiError> {
+        let (parser, ()) = with_fallback(parser, |parser| {
+            let mut transaction = self.session.start_transaction()?;
+            prepare(&mut transaction)?;
+            let prefixes = parser.load_into(&mut transaction)?;
+            record_prefixes(&mut transaction, parser.graph.as_ref(), &prefixes)?;
+            Ok(transaction.commit()?)
+        })?;
+        self.invalidate(parser.graph.as_str());
+        Ok(parser.resource_type)
+    }
+
+    /// Replace the content of `graph` with `fs` in a single transaction, so
+    /// that either all of the old quads or none of them remain.
+    pub fn replace_graph(
+        &self,
+        fs: &Path,
+        graph: &str,
+        base_iri: Option<&str>,
+        lenient: bool,
+    ) -> Result<(), HornedOxiError> {
+        self.replace(parser_for_graph(
+            fs,
+            NamedNodeRef::new(graph)?,
+            base_iri,
+            lenient,
+        )?)?;
+        Ok(())
+    }
+
+    /// Replace the content of `graph` with an ontology held in memory, in a
+    /// single transaction. The syntax is detected from the content, trying
+    /// `hint` first; relative IRIs are resolved against the graph name.
+    /// Returns the type the ontology was read as.
+    pub fn replace_graph_with_bytes(
+        &self,
+        bytes: Vec<u8>,
+        hint: Option<ResourceType>,
+        graph: &str,
+        lenient: bool,
+    ) -> Result<ResourceType, HornedOxiError> {
+        self.replace(parser_for_detected_input(
+            ParserInput::Buffer(Cursor::new(bytes)),
+            hint,
+            NamedNodeRef::new(graph)?,
+            Some(graph),
+            lenient,
+        )?)
+    }
+
+    fn replace(&self, parser: PreparedParser) -> Result<ResourceType, HornedOxiError> {
+        let graph = parser.graph.clone();
+        self.load_with(parser, |transaction| {
+            transaction.remove_named_graph(&graph)?;
+            let old = transaction
+                .quads_for_pattern(
+                    Some(graph.as_ref().into()),
+                    Some(PREFIX),
+                    None,
+                    Some(METADATA_GRAPH.into()),
+                )
+                .collect::<Result<Vec<_>, _>>()?;
+            for quad in &old {
+                transaction.remove(quad);
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove `graph` and its quads. Returns `false` if there was no such
+    /// graph.
+    pub fn remove_graph(&self, graph: &str) -> Result<bool, HornedOxiError> {
+        let graph = NamedNodeRef::new(graph)?;
+        if !self.session.contains_named_graph(graph)? {
+            return Ok(false);
+        }
+        let mut transaction = self.session.start_transaction()?;
+        clear_graph(&mut transaction, graph)?;
+        transaction.commit()?;
+        self.invalidate(graph.as_str());
+        Ok(true)
+    }
+
+    /// Move the quads of `from` into `to` in a single transaction. Fails if
+    /// there is no graph `from`, or if `to` already exists and `overwrite`
+    /// is not set; with `overwrite`, the quads and metadata of `to` are
+    /// removed first.
+    pub fn rename_graph(
+        &self,
+        from: &str,
+        to: &str,
+        overwrite: bool,
+    ) -> Result<(), HornedOxiError> {
+        let from = NamedNodeRef::new(from)?;
+        let to = NamedNodeRef::new(to)?;
+        let mut transaction = self.session.start_transaction()?;
+        if !transaction.contains_named_graph(from)? {
+            return Err(HornedOxiErrorKind::InvalidInput(format!(
+                "No graph named {}",
+                from.as_str()
+            ))
+            .into());
+        }
+        if from == to {
+            return Ok(());
+        }
+        if transaction.contains_named_graph(to)? {
+            if !overwrite {
+                return Err(HornedOxiErrorKind::InvalidInput(format!(
+                    "Cannot rename {} to {}, which already exists",
+                    from.as_str(),
+                    to.as_str()
+                ))
+                .into());
+            }
+            clear_graph(&mut transaction, to)?;
+        }
+        let quads = transaction
+            .quads_for_pattern(None, None, None, Some(from.into()))
             .collect::<Result<Vec<_>, _>>()?;
+        for quad in &quads {
+            transaction.insert(QuadRef::new(
+                &quad.subject,
+                &quad.predicate,
+                &quad.object,
+                to,
+            ));
+        }
+        transaction.remove_named_graph(from)?;
+        let metadata = transaction
+            .quads_for_pattern(Some(from.into()), None, None, Some(METADATA_GRAPH.into()))
+            .collect::<Result<Vec<_>, _>>()?;
+        for quad in &metadata {
+            transaction.remove(quad);
+            transaction.insert(QuadRef::new(
+                to,
+                &quad.predicate,
+                &quad.object,
+                METADATA_GRAPH,
+            ));
+        }
+        transaction.commit()?;
+        self.invalidate(from.as_str());
+        self.invalidate(to.as_str());
+        Ok(())
+    }
 
-        let mut buf = Vec::new();
-        let mut serializer = RdfSerializer::from_format(RdfFormat::RdfXml).for_writer(&mut buf);
-        for quad in quads {
-            serializer.serialize_triple(quad.as_ref())?;
+    /// The named graphs of the store, with the ontology each one declares.
+    pub fn list_ontologies(&self) -> Result<Vec<LoadedOntology>, HornedOxiError> {
+        let mut ontologies = Vec::new();
+        for graph in self.session.named_graphs() {
+            let NamedOrBlankNode::NamedNode(graph) = graph? else {
+                continue;
+            };
+            if graph == METADATA_GRAPH {
+                continue;
+            }
+            let iri = self.ontology_iri(&graph)?;
+            let version_iri = match &iri {
+                Some(iri) => self.version_iri(&graph, iri)?,
+                None => None,
+            };
+            ontologies.push(LoadedOntology {
+                graph: graph.into_string(),
+                iri,
+                version_iri,
+            });
         }
-        let ontology = rdf::reader::read::<BufReader<Cursor<Vec<u8>>>>(
-            &mut BufReader::new(Cursor::new(buf)),
-            ParserConfiguration::default(),
-        )?;
+        Ok(ontologies)
+    }
+
+    /// Insert `fs` and, recursively, the ontologies it imports, each into its
+    /// own graph. Imports are resolved through `catalog`; problems with
+    /// imports are reported in the result rather than failing the load.
+    pub fn insert_file_with_imports(
+        &self,
+        fs: &Path,
+        lenient: bool,
+        catalog: &Catalog,
+    ) -> Result<ImportClosure, HornedOxiError> {
+        let mut closure = ImportClosure::default();
+        self.insert_closure(fs, None, lenient, catalog, &mut vec![], &mut closure)?;
+        Ok(closure)
+    }
+
+    /// Load `fs`, imported as `imported_as`, and then its imports. An
+    /// import is a cycle if it names an ontology on `stack` by any of its
+    /// IRIs, or resolves to the file of one.
+    fn insert_closure(
+        &self,
+        fs: &Path,
+        imported_as: Option<String>,
+        lenient: bool,
+        catalog: &Catalog,
+        stack: &mut Vec<Importing>,
+        closure: &mut ImportClosure,
+    ) -> Result<(), HornedOxiError> {
+        let graph = file_iri(fs)?;
+        self.insert_file(fs, lenient)?;
+        let graph_node = NamedNode::new(graph.as_str())?;
+        let iri = self
+            .ontology_iri(&graph_node)?
+            .unwrap_or_else(|| graph.clone());
+        closure.graphs.push((iri.clone(), graph.clone()));
+        let mut names = vec![iri.clone()];
+        names.extend(self.version_iri(&graph_node, &iri)?);
+        names.extend(imported_as);
+        stack.push(Importing { iri, graph, names });
+        for import in self.imports(&graph_node)? {
+            let path = catalog.resolve(&import);
+            let path_graph = path.as_deref().and_then(|path| file_iri(path).ok());
+            if let Some(start) = stack.iter().position(|importing| {
+                importing.names.contains(&import) || path_graph.as_ref() == Some(&importing.graph)
+            }) {
+                let mut cycle = stack[start..]
+                    .iter()
+                    .map(|importing| importing.iri.clone())
+                    .collect::<Vec<_>>();
+                cycle.push(import);
+                closure.cycles.push(cycle);
+                continue;
+            }
+            if closure
+                .graphs
+                .iter()
+                .any(|(iri, graph)| *iri == import || path_graph.as_ref() == Some(graph))
+            {
+                continue;
+            }
+            match path {
+                Some(path) => {
+                    if let Err(error) = self.insert_closure(
+                        &path,
+                        Some(import.clone()),
+                        lenient,
+                        catalog,
+                        stack,
+                        closure,
+                    ) {
+                        closure.failed.push((import, error));
+                    }
+                }
+                None => closure.missing.push(import),
+            }
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// The IRI of the `owl:Ontology` declared in `graph`.
+    fn ontology_iri(&self, graph: &NamedNode) -> Result<Option<String>, HornedOxiError> {
+        let ontology = NamedNodeRef::new_unchecked(OWL::Ontology.as_ref());
+        for quad in self.session.quads_for_pattern(
+            None,
+            Some(TYPE),
+            Some(ontology.into()),
+            Some(graph.into()),
+        ) {
+            if let NamedOrBlankNode::NamedNode(iri) = quad?.subject {
+                return Ok(Some(iri.into_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    fn version_iri(
+        &self,
+        graph: &NamedNode,
+        ontology: &str,
+    ) -> Result<Option<String>, HornedOxiError> {
+        let version = NamedNodeRef::new_unchecked(OWL::VersionIRI.as_ref());
+        for quad in self.session.quads_for_pattern(
+            Some(NamedNodeRef::new(ontology)?.into()),
+            Some(version),
+            None,
+            Some(graph.into()),
+        ) {
+            if let Term::NamedNode(iri) = quad?.object {
+                return Ok(Some(iri.into_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The IRIs imported by the ontology in `graph`.
+    fn imports(&self, graph: &NamedNode) -> Result<Vec<String>, HornedOxiError> {
+        let imports = NamedNodeRef::new_unchecked(OWL::Imports.as_ref());
+        let mut iris = Vec::new();
+        for quad in self
+            .session
+            .quads_for_pattern(None, Some(imports), None, Some(graph.into()))
+        {
+            if let Term::NamedNode(iri) = quad?.object {
+                iris.push(iri.into_string());
+            }
+        }
+        Ok(iris)
+    }
+
+    /// The ontology loaded from `fs` by `insert_file`.
+    pub fn get_file_ontology(&self, fs: &Path) -> Result<SetOntology<RcStr>, HornedOxiError> {
+        self.get_ontology(file_iri(fs)?)
+    }
+
+    /// Read the graph `ontology_id` with the horned-owl RDF reader. The
+    /// reader holds every triple of the graph; see [`crate::rdf_xml`] for
+    /// the graphs it cannot read.
+    pub fn get_ontology(&self, ontology_id: String) -> Result<SetOntology<RcStr>, HornedOxiError> {
+        let graph = NamedNodeRef::new(&ontology_id)?;
+        let ontology = self.read_graph(graph, ParserConfiguration::default())?;
         Ok(ontology.0.into())
     }
 
+    /// As [`Self::get_ontology`], together with what the reader left over.
+    /// The graph is read leniently, so that triples the reader cannot
+    /// interpret are reported rather than failing the read.
+    pub fn get_ontology_with_diagnostics(
+        &self,
+        ontology_id: String,
+    ) -> Result<(SetOntology<RcStr>, ParseDiagnostics<RcStr>), HornedOxiError> {
+        let graph = NamedNodeRef::new(&ontology_id)?;
+        let mut config = ParserConfiguration::default();
+        config.rdf.lax = true;
+        let (ontology, incomplete) = self.read_graph(graph, config)?;
+
+        let mut bnodes = HashMap::new();
+        let blank_nodes = incomplete
+            .bnode
+            .iter()
+            .map(|triples| {
+                triples
+                    .iter()
+                    .filter_map(|triple| rdf_triple(triple, &mut bnodes))
+                    .collect()
+            })
+            .collect();
+        let lists = incomplete
+            .bnode_seq
+            .iter()
+            .map(|list| {
+                list.iter()
+                    .map(|term| rdf_term(term, &mut bnodes))
+                    .collect()
+            })
+            .collect();
+        let annotations = incomplete
+            .ann_map
+            .into_iter()
+            .filter_map(|(triple, annotations)| {
+                rdf_triple(&triple, &mut bnodes).map(|triple| (triple, annotations))
+            })
+            .collect();
+        let diagnostics = ParseDiagnostics {
+            simple_triples: incomplete.simple.len(),
+            blank_nodes,
+            lists,
+            class_expressions: incomplete.class_expression,
+            object_property_expressions: incomplete.object_property_expression,
+            data_ranges: incomplete.data_range,
+            atoms: incomplete.atom.into_values().collect(),
+            annotations,
+        };
+        Ok((ontology.into(), diagnostics))
+    }
+
+    fn read_graph(
+        &self,
+        graph: NamedNodeRef<'_>,
+        config: ParserConfiguration,
+    ) -> Result<
+        (
+            ConcreteRDFOntology<RcStr, RcAnnotatedComponent>,
+            IncompleteParse<RcStr>,
+        ),
+        HornedOxiError,
+    > {
+        read_quads(
+            self.session
+                .quads_for_pattern(None, None, None, Some(graph.into())),
+            config,
+        )
+    }
+
+    /// As [`Self::get_file_ontology`], together with what the reader left
+    /// over.
+    pub fn get_file_ontology_with_diagnostics(
+        &self,
+        fs: &Path,
+    ) -> Result<(SetOntology<RcStr>, ParseDiagnostics<RcStr>), HornedOxiError> {
+        self.get_ontology_with_diagnostics(file_iri(fs)?)
+    }
+
+    /// Extract the graph `graph` for drawing straight from the store with
+    /// SPARQL, without reading it into an ontology first. Fails if there is
+    /// no such graph.
+    pub fn extract_graph(
+        &self,
+        graph: &str,
+        config: ExtractConfiguration,
+    ) -> Result<HornedVOWLExtract<A>, HornedOxiError> {
+        self.require_graph(graph)?;
+        let config = self.declared_prefixes(graph, config)?;
+        SparqlReader::new(&self.session, NamedNodeRef::new(graph)?).extract(config)
+    }
+
+    /// Read the graph `graph` into an ontology and extract it for drawing.
+    /// Fails if there is no such graph.
+    pub fn extract_ontology(
+        &self,
+        graph: &str,
+        config: ExtractConfiguration,
+    ) -> Result<HornedVOWLExtract<RcStr>, HornedOxiError> {
+        self.require_graph(graph)?;
+        let config = self.declared_prefixes(graph, config)?;
+        let ontology = self.get_ontology(graph.to_string())?;
+        Ok(HornedVOWLExtract::with_config(&ontology, config))
+    }
+
+    /// `config` with the prefixes declared by the files loaded into
+    /// `graph`, unless it lists its own. The namespace of the default
+    /// prefix counts as internal, so that an ontology such as
+    /// `http://ex.org/onto.owl` declaring `http://ex.org/` owns the entities
+    /// in it.
+    fn declared_prefixes(
+        &self,
+        graph: &str,
+        mut config: ExtractConfiguration,
+    ) -> Result<ExtractConfiguration, HornedOxiError> {
+        let prefixes = self.prefixes(graph)?;
+        for (name, iri) in &prefixes {
+            if name.is_empty() && !config.internal_namespaces.contains(iri) {
+                config.internal_namespaces.push(iri.clone());
+            }
+        }
+        if config.prefixes.is_empty() {
+            config.prefixes = prefixes;
+        }
+        Ok(config)
+    }
+
+    /// Write the graph `graph` to `writer` in the syntax of
+    /// `resource_type`, declaring the prefixes of the files it was loaded
+    /// from. OFN and OWX are written by horned-owl from the ontology read
+    /// from the graph, the RDF syntaxes straight from the quads; N3 is
+    /// written as Turtle.
+    pub fn export_ontology(
+        &self,
+        graph: &str,
+        resource_type: ResourceType,
+        mut writer: impl Write,
+    ) -> Result<(), HornedOxiError> {
+        let graph = NamedNodeRef::new(graph)?;
+        let prefixes = self.prefixes(graph.as_str())?;
+        match resource_type {
+            ResourceType::OFN | ResourceType::OWX => {
+                let ontology = RcComponentMappedOntology::from(
+                    self.read_graph(graph, ParserConfiguration::default())?.0,
+                );
+                let mut mapping = PrefixMapping::default();
+                for (name, iri) in &prefixes {
+                    // The OWX writer declares each prefix as an XML
+                    // namespace, which cannot be unnamed.
+                    if name.is_empty() && resource_type == ResourceType::OWX {
+                        continue;
+                    }
+                    // `_` names blank nodes and cannot be a prefix.
+                    let _ = mapping.add_prefix(name, iri);
+                }
+                if resource_type == ResourceType::OFN {
+                    ofn::writer::write(writer, &ontology, Some(&mapping))?;
+                } else {
+                    owx::writer::write(writer, &ontology, Some(&mapping))?;
+                }
+            }
+            _ => {
+                let format = match resource_type {
+                    ResourceType::N3 => RdfFormat::Turtle,
+                    resource_type => rdf_format(resource_type),
+                };
+                let mut serializer = RdfSerializer::from_format(format);
+                for (name, iri) in &prefixes {
+                    serializer = serializer.with_prefix(name, iri)?;
+                }
+                let mut serializer = serializer.for_writer(&mut writer);
+                for quad in self
+                    .session
+                    .quads_for_pattern(None, None, None, Some(graph.into()))
+                {
+                    let quad = quad?;
+                    if format.supports_datasets() {
+                        serializer.serialize_quad(&quad)?;
+                    } else {
+                        serializer.serialize_triple(TripleRef::from(quad.as_ref()))?;
+                    }
+                }
+                serializer.finish()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The VOWL JSON of `graph`. It is converted on first use and then
+    /// kept until the graph is changed through this store; changes made
+    /// through [`Self::session`] directly go unnoticed. Fails if there is
+    /// no such graph.
+    pub fn vowl_json(&self, graph: &str) -> Result<Arc<str>, HornedOxiError> {
+        let generation = {
+            let extracts = self.extracts();
+            if let Some(json) = extracts.json.get(graph) {
+                return Ok(json.clone());
+            }
+            extracts.generation(graph)
+        };
+        let extract = self.extract_ontology(graph, ExtractConfiguration::default())?;
+        let json = Arc::<str>::from(serde_json::to_string_pretty(&extract.to_vowl())?);
+        let mut extracts = self.extracts();
+        if extracts.generation(graph) == generation {
+            extracts.json.insert(graph.to_string(), json.clone());
+        }
+        Ok(json)
+    }
+
+    /// Drop the cached VOWL JSON of `graph`, and any still being converted.
+    fn invalidate(&self, graph: &str) {
+        let mut extracts = self.extracts();
+        extracts.json.remove(graph);
+        *extracts.generations.entry(graph.to_string()).or_default() += 1;
+    }
+
+    fn extracts(&self) -> MutexGuard<'_, Extracts> {
+        // The cache is only ever added to or removed from whole, so it is
+        // still consistent if a holder panicked.
+        self.extracts.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Evaluate the SPARQL query `query` against `scope`. Any dataset the
+    /// query names with `FROM` is replaced by the scope.
+    pub fn query(&self, query: &str, scope: GraphScope<'_>) -> Result<QueryResult, HornedOxiError> {
+        let graphs = self.scope_graphs(scope)?;
+        let mut query = SparqlEvaluator::new().parse_query(query)?;
+        query
+            .dataset_mut()
+            .set_default_graph(graphs.iter().cloned().map(Into::into).collect());
+        query
+            .dataset_mut()
+            .set_available_named_graphs(graphs.into_iter().map(NamedOrBlankNode::from).collect());
+        Ok(match query.on_store(&self.session).execute()? {
+            QueryResults::Solutions(solutions) => QueryResult::Solutions {
+                variables: solutions.variables().to_vec(),
+                solutions: solutions.collect::<Result<_, _>>()?,
+            },
+            QueryResults::Boolean(value) => QueryResult::Boolean(value),
+            QueryResults::Graph(triples) => QueryResult::Graph(triples.collect::<Result<_, _>>()?),
+        })
+    }
+
+    /// Evaluate the SPARQL update `update` against `scope`, and drop the
+    /// cached VOWL JSON of the graphs it may have changed, which are
+    /// returned.
+    ///
+    /// Within a single graph, the default graph of the update is that
+    /// graph, and writing to any other graph is an error. Over the union of
+    /// graphs, `WHERE` clauses read all of them, but writes have to name
+    /// their graph with `GRAPH`; `CLEAR ALL` and `DROP ALL` apply to each
+    /// graph holding an ontology. The metadata graph cannot be written,
+    /// and any dataset named with `USING` is replaced by the scope.
+    /// Solutions that bind a `GRAPH ?g` of a template to a graph outside
+    /// the scope write nothing to it.
+    pub fn update(
+        &self,
+        update: &str,
+        scope: GraphScope<'_>,
+    ) -> Result<Vec<String>, HornedOxiError> {
+        let graphs = self.scope_graphs(scope)?;
+        let update = SparqlParser::new().parse_update(update)?;
+        let mut scoped = Update {
+            base_iri: update.base_iri,
+            operations: Vec::new(),
+        };
+        let mut changed = BTreeSet::new();
+        for operation in update.operations {
+            scope_operation(
+                operation,
+                scope,
+                &graphs,
+                &mut scoped.operations,
+                &mut changed,
+            )?;
+        }
+        SparqlEvaluator::new()
+            .for_update(scoped)
+            .on_store(&self.session)
+            .execute()?;
+        let changed = changed
+            .into_iter()
+            .map(NamedNode::into_string)
+            .collect::<Vec<_>>();
+        for graph in &changed {
+            self.invalidate(graph);
+        }
+        Ok(changed)
+    }
+
+    /// The graphs making up `scope`.
+    fn scope_graphs(&self, scope: GraphScope<'_>) -> Result<Vec<NamedNode>, HornedOxiError> {
+        match scope {
+            GraphScope::Graph(graph) => Ok(vec![NamedNode::new(graph)?]),
+            GraphScope::Union => Ok(self
+                .list_ontologies()?
+                .into_iter()
+                .map(|ontology| NamedNode::new_unchecked(ontology.graph))
+                .collect()),
+        }
+    }
+
     pub fn convert_ontology(
         &self,
         ontology: SetOntology<RcStr>,
     ) -> Result<HornedVOWLExtract<RcStr>, HornedOxiError> {
-        //let cmpontology = RcComponentMappedOntology::from(ontology);
-        let horned_oxi: HornedVOWLExtract<RcStr> = ontology.into();
-        //println!("{}", horned_oxi);
-        Ok(horned_oxi)
+        Ok(ontology.into())
     }
 }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ResourceType {
     OFN,
     OWX,
@@ -92,13 +1079,12 @@ pub enum ParserInput {
     Buffer(Cursor<Vec<u8>>),
 }
 impl ParserInput {
-    fn from_path(path: &Path) -> Result<Self, HornedOxiError> {
-        std::fs::read(path)
-            .map(ParserInput::File)
-            .map_err(HornedOxiError::from)
+    /// The content of `path`, decompressed according to its extensions.
+    pub fn from_path(path: &Path) -> Result<Self, HornedOxiError> {
+        Ok(ParserInput::File(decompress(std::fs::read(path)?, path)?))
     }
 
-    fn as_slice(&self) -> &[u8] {
+    pub fn as_slice(&self) -> &[u8] {
         match self {
             ParserInput::Buffer(cursor) => cursor.get_ref().as_slice(),
             ParserInput::File(bytes) => bytes.as_slice(),
@@ -106,128 +1092,939 @@ impl ParserInput {
     }
 }
 
-pub struct PreparedParser {
-    pub parser: RdfParser,
-    pub input: ParserInput,
+pub struct PreparedParser {
+    pub parser: RdfParser,
+    pub input: ParserInput,
+    /// The type the input is read as, which may differ from the one its
+    /// extension suggests.
+    pub resource_type: ResourceType,
+    /// The graph the default graph of the input goes into.
+    pub graph: NamedNode,
+    /// The prefixes an OFN or OWX input declares, as name and IRI. Those
+    /// of the RDF syntaxes are read as the input is loaded.
+    pub prefixes: Vec<(String, String)>,
+    fallback: Fallback,
+}
+
+/// What is needed to try the input as another type; see
+/// [`parser_for_detected_input`].
+#[derive(Default)]
+struct Fallback {
+    /// The input before it was converted from OFN or OWX.
+    source: Option<ParserInput>,
+    candidates: Vec<ResourceType>,
+    base_iri: Option<String>,
+    lenient: bool,
+}
+
+impl PreparedParser {
+    /// Insert the quads of the input into `transaction`, returning the
+    /// prefixes it declares.
+    fn load_into(&self, transaction: &mut Transaction<'_>) -> Result<Prefixes, HornedOxiError> {
+        let mut quads = self.parser.clone().for_slice(self.input.as_slice());
+        for quad in quads.by_ref() {
+            transaction.insert(&quad?);
+        }
+        Ok(self.declared_prefixes(&quads))
+    }
+
+    /// Load the quads of the input with `loader`, returning the prefixes it
+    /// declares. N-Triples and N-Quads, which declare none, are parsed in
+    /// parallel.
+    fn bulk_load_into(&self, loader: &mut BulkLoader) -> Result<Prefixes, HornedOxiError> {
+        if matches!(
+            self.parser.format(),
+            RdfFormat::NTriples | RdfFormat::NQuads
+        ) {
+            loader.parallel_load_from_slice(self.parser.clone(), self.input.as_slice())?;
+            return Ok(Vec::new());
+        }
+        let mut quads = self
+            .parser
+            .clone()
+            .rename_blank_nodes()
+            .for_slice(self.input.as_slice());
+        loader.load_ok_quads::<_, HornedOxiError>(quads.by_ref())?;
+        Ok(self.declared_prefixes(&quads))
+    }
+
+    /// The quads of the input, with the prefixes it declares.
+    fn read(&self) -> Result<(Vec<Quad>, Prefixes), HornedOxiError> {
+        let mut quads = self.parser.clone().for_slice(self.input.as_slice());
+        let read = quads.by_ref().collect::<Result<Vec<_>, _>>()?;
+        Ok((read, self.declared_prefixes(&quads)))
+    }
+
+    /// The prefixes of the input, once `quads` has been read through.
+    fn declared_prefixes(&self, quads: &SliceQuadParser<'_>) -> Prefixes {
+        match self.resource_type {
+            ResourceType::OFN | ResourceType::OWX => self.prefixes.clone(),
+            _ => prefix_list(quads.prefixes()),
+        }
+    }
+
+    /// A parser for the next candidate type the input may be of, once it
+    /// did not read as `resource_type`.
+    fn fallback(self) -> Option<Result<PreparedParser, HornedOxiError>> {
+        let Fallback {
+            source,
+            candidates,
+            base_iri,
+            lenient,
+        } = self.fallback;
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(prepare_candidates(
+            source.unwrap_or(self.input),
+            candidates,
+            self.graph.as_ref(),
+            base_iri.as_deref(),
+            lenient,
+        ))
+    }
+}
+
+/// Run `attempt` with `parser`, and while it fails, with a parser for each
+/// of the other candidate types the input may be of in turn. Fails with
+/// the error of the first attempt.
+fn with_fallback<T>(
+    mut parser: PreparedParser,
+    mut attempt: impl FnMut(&PreparedParser) -> Result<T, HornedOxiError>,
+) -> Result<(PreparedParser, T), HornedOxiError> {
+    let mut first_error = None;
+    loop {
+        match attempt(&parser) {
+            Ok(value) => return Ok((parser, value)),
+            Err(error) => {
+                let error = first_error.take().unwrap_or(error);
+                match parser.fallback() {
+                    Some(Ok(next)) => {
+                        parser = next;
+                        first_error = Some(error);
+                    }
+                    _ => return Err(error),
+                }
+            }
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(feature = "rocksdb")]
+fn copy_dir(from: &Path, to: &Path) -> Result<(), HornedOxiError> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// A compression layer, recognised by the extension of a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Bzip2,
+    Zstd,
+}
+
+impl Compression {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|s| s.to_str()) {
+            Some("gz") => Some(Compression::Gzip),
+            Some("bz2") => Some(Compression::Bzip2),
+            Some("zst") => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn decompress(self, bytes: &[u8]) -> Result<Vec<u8>, HornedOxiError> {
+        self.decompress_at_most(bytes, u64::MAX)
+    }
+
+    /// As [`Self::decompress`], failing once the output grows beyond
+    /// `limit` bytes.
+    fn decompress_at_most(self, bytes: &[u8], limit: u64) -> Result<Vec<u8>, HornedOxiError> {
+        match self {
+            Compression::Gzip => read_at_most(flate2::read::MultiGzDecoder::new(bytes), limit),
+            Compression::Bzip2 => read_at_most(bzip2::read::MultiBzDecoder::new(bytes), limit),
+            Compression::Zstd => read_at_most(
+                ruzstd::decoding::StreamingDecoder::new(bytes).map_err(std::io::Error::other)?,
+                limit,
+            ),
+        }
+    }
+}
+
+/// The largest size an archive entry may have once decompressed, so that
+/// a small archive cannot exhaust memory.
+pub const MAX_ARCHIVE_ENTRY_SIZE: u64 = 1 << 30;
+
+/// Read all of `reader`, failing if it holds more than `limit` bytes.
+fn read_at_most(reader: impl Read, limit: u64) -> Result<Vec<u8>, HornedOxiError> {
+    let mut buf = Vec::new();
+    reader.take(limit.saturating_add(1)).read_to_end(&mut buf)?;
+    if buf.len() as u64 > limit {
+        return Err(HornedOxiErrorKind::InvalidInput(format!(
+            "content is larger than {} bytes",
+            limit
+        ))
+        .into());
+    }
+    Ok(buf)
+}
+
+/// `path` without its compression extensions, so `a.owl.gz` becomes
+/// `a.owl`.
+pub fn uncompressed_path(path: &Path) -> &Path {
+    let mut path = path;
+    while Compression::from_path(path).is_some() {
+        match path.file_stem() {
+            Some(stem) => path = Path::new(stem),
+            None => break,
+        }
+    }
+    path
+}
+
+/// Strip the compression layers named by the extensions of `path`, the
+/// outermost first.
+fn decompress(bytes: Vec<u8>, path: &Path) -> Result<Vec<u8>, HornedOxiError> {
+    decompress_at_most(bytes, path, u64::MAX)
+}
+
+/// As [`decompress`], failing once a layer grows beyond `limit` bytes.
+fn decompress_at_most(
+    mut bytes: Vec<u8>,
+    path: &Path,
+    limit: u64,
+) -> Result<Vec<u8>, HornedOxiError> {
+    let mut path = path;
+    while let Some(compression) = Compression::from_path(path) {
+        bytes = compression.decompress_at_most(&bytes, limit)?;
+        path = Path::new(path.file_stem().unwrap_or_default());
+    }
+    Ok(bytes)
+}
+
+/// Whether `path` is an archive to be loaded with
+/// [`HornedOxiStore::insert_archive`].
+pub fn is_archive(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "zip")
+}
+
+/// The name of the graph holding `entry` of `archive`, in the form
+/// `jar:file:///path/to/archive.zip!/entry`.
+pub fn archive_entry_iri(archive: &Path, entry: &Path) -> Result<String, HornedOxiError> {
+    let mut iri = format!("jar:{}!", file_iri(archive)?);
+    for component in entry.components() {
+        if let Component::Normal(segment) = component {
+            iri.push('/');
+            percent_encode(&mut iri, segment);
+        }
+    }
+    Ok(iri)
+}
+
+/// The type of `path` judged by its extension, looking through any
+/// compression extensions.
+pub fn path_type(path: &Path) -> Option<ResourceType> {
+    extension_type(uncompressed_path(path).extension()?.to_str()?)
+}
+
+/// The type of a file with the extension `extension`, such as `ttl`.
+pub fn extension_type(extension: &str) -> Option<ResourceType> {
+    match extension {
+        "ofn" => Some(ResourceType::OFN),
+        "owx" => Some(ResourceType::OWX),
+        "rdf" => Some(ResourceType::RDF),
+        "owl" => Some(ResourceType::OWL),
+        "ttl" => Some(ResourceType::TTL),
+        "nt" => Some(ResourceType::NTriples),
+        "nq" => Some(ResourceType::NQuads),
+        "trig" => Some(ResourceType::TriG),
+        "jsonld" => Some(ResourceType::JsonLd),
+        "n3" => Some(ResourceType::N3),
+        _ => None,
+    }
+}
+
+/// Push `operation` to `scoped`, rewritten to act within `graphs` as
+/// described at [`HornedOxiStore::update`], and add the graphs it may write
+/// to `changed`.
+fn scope_operation(
+    operation: GraphUpdateOperation,
+    scope: GraphScope<'_>,
+    graphs: &[NamedNode],
+    scoped: &mut Vec<GraphUpdateOperation>,
+    changed: &mut BTreeSet<NamedNode>,
+) -> Result<(), HornedOxiError> {
+    // The graph to write to in place of `graph`, which is `None` for the
+    // default graph.
+    let mut target = |graph: Option<NamedNode>| -> Result<NamedNode, HornedOxiError> {
+        let graph = match (graph, scope) {
+            (None, GraphScope::Graph(_)) => graphs[0].clone(),
+            (None, GraphScope::Union) => {
+                return Err(HornedOxiErrorKind::InvalidInput(
+                    "Updates over the union of graphs must name the graph to write with GRAPH"
+                        .to_string(),
+                )
+                .into());
+            }
+            (Some(graph), _) => graph,
+        };
+        if graph == METADATA_GRAPH || matches!(scope, GraphScope::Graph(_)) && graph != graphs[0] {
+            return Err(HornedOxiErrorKind::InvalidInput(format!(
+                "The update writes to {}, outside of its scope",
+                graph
+            ))
+            .into());
+        }
+        changed.insert(graph.clone());
+        Ok(graph)
+    };
+    match operation {
+        GraphUpdateOperation::InsertData { mut data } => {
+            for quad in &mut data {
+                let graph = match &quad.graph_name {
+                    GraphName::NamedNode(graph) => Some(graph.clone()),
+                    _ => None,
+                };
+                quad.graph_name = target(graph)?.into();
+            }
+            scoped.push(GraphUpdateOperation::InsertData { data });
+        }
+        GraphUpdateOperation::DeleteData { mut data } => {
+            for quad in &mut data {
+                let graph = match &quad.graph_name {
+                    GraphName::NamedNode(graph) => Some(graph.clone()),
+                    _ => None,
+                };
+                quad.graph_name = target(graph)?.into();
+            }
+            scoped.push(GraphUpdateOperation::DeleteData { data });
+        }
+        GraphUpdateOperation::DeleteInsert {
+            mut delete,
+            mut insert,
+            mut pattern,
+            ..
+        } => {
+            let mut variables = BTreeSet::new();
+            let graph_names = delete
+                .iter_mut()
+                .map(|quad| &mut quad.graph_name)
+                .chain(insert.iter_mut().map(|quad| &mut quad.graph_name));
+            for graph_name in graph_names {
+                *graph_name = match graph_name {
+                    GraphNamePattern::NamedNode(graph) => {
+                        GraphNamePattern::NamedNode(target(Some(graph.clone()))?)
+                    }
+                    GraphNamePattern::DefaultGraph => GraphNamePattern::NamedNode(target(None)?),
+                    GraphNamePattern::Variable(variable) => {
+                        variables.insert(variable.clone());
+                        GraphNamePattern::Variable(variable.clone())
+                    }
+                };
+            }
+            // `BIND` or `VALUES` could set a graph variable to any graph, so
+            // solutions binding one outside the scope are left out.
+            for variable in variables {
+                let allowed = Expression::In(
+                    Box::new(Expression::Variable(variable.clone())),
+                    graphs.iter().cloned().map(Expression::NamedNode).collect(),
+                );
+                pattern = Box::new(GraphPattern::Filter {
+                    expr: Expression::Or(
+                        Box::new(Expression::Not(Box::new(Expression::Bound(variable)))),
+                        Box::new(allowed),
+                    ),
+                    inner: pattern,
+                });
+                changed.extend(graphs.iter().cloned());
+            }
+            scoped.push(GraphUpdateOperation::DeleteInsert {
+                delete,
+                insert,
+                using: Some(QueryDataset {
+                    default: graphs.to_vec(),
+                    named: Some(graphs.to_vec()),
+                }),
+                pattern,
+            });
+        }
+        GraphUpdateOperation::Load {
+            silent,
+            source,
+            destination,
+        } => {
+            let graph = match destination {
+                GraphName::NamedNode(graph) => Some(graph),
+                _ => None,
+            };
+            scoped.push(GraphUpdateOperation::Load {
+                silent,
+                source,
+                destination: target(graph)?.into(),
+            });
+        }
+        GraphUpdateOperation::Create { silent, graph } => {
+            scoped.push(GraphUpdateOperation::Create {
+                silent,
+                graph: target(Some(graph))?,
+            });
+        }
+        GraphUpdateOperation::Clear { silent, graph } => {
+            for graph in scope_target(graph, graphs, &mut target)? {
+                scoped.push(GraphUpdateOperation::Clear {
+                    silent,
+                    graph: GraphTarget::NamedNode(graph),
+                });
+            }
+        }
+        GraphUpdateOperation::Drop { silent, graph } => {
+            for graph in scope_target(graph, graphs, &mut target)? {
+                scoped.push(GraphUpdateOperation::Drop {
+                    silent,
+                    graph: GraphTarget::NamedNode(graph),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The graphs `CLEAR` or `DROP` of `graph` act on within `scope`.
+fn scope_target(
+    graph: GraphTarget,
+    graphs: &[NamedNode],
+    target: &mut impl FnMut(Option<NamedNode>) -> Result<NamedNode, HornedOxiError>,
+) -> Result<Vec<NamedNode>, HornedOxiError> {
+    match graph {
+        GraphTarget::NamedNode(graph) => Ok(vec![target(Some(graph))?]),
+        GraphTarget::DefaultGraph => Ok(vec![target(None)?]),
+        GraphTarget::NamedGraphs | GraphTarget::AllGraphs => graphs
+            .iter()
+            .map(|graph| target(Some(graph.clone())))
+            .collect(),
+    }
+}
+
+/// Remove `graph` with its quads and its metadata.
+fn clear_graph(
+    transaction: &mut Transaction<'_>,
+    graph: NamedNodeRef<'_>,
+) -> Result<(), HornedOxiError> {
+    transaction.remove_named_graph(graph)?;
+    let metadata = transaction
+        .quads_for_pattern(Some(graph.into()), None, None, Some(METADATA_GRAPH.into()))
+        .collect::<Result<Vec<_>, _>>()?;
+    for quad in &metadata {
+        transaction.remove(quad);
+    }
+    Ok(())
+}
+
+/// Record `prefixes` for `graph` in the metadata graph, replacing those of
+/// the same name.
+fn record_prefixes(
+    transaction: &mut Transaction<'_>,
+    graph: NamedNodeRef<'_>,
+    prefixes: &[(String, String)],
+) -> Result<(), HornedOxiError> {
+    let names = prefixes
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<BTreeSet<_>>();
+    let replaced = transaction
+        .quads_for_pattern(
+            Some(graph.into()),
+            Some(PREFIX),
+            None,
+            Some(METADATA_GRAPH.into()),
+        )
+        .filter(|quad| match quad {
+            Ok(quad) => match &quad.object {
+                Term::Literal(prefix) => prefix
+                    .value()
+                    .split_once('=')
+                    .is_some_and(|(name, _)| names.contains(name)),
+                _ => false,
+            },
+            Err(_) => true,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    for quad in &replaced {
+        transaction.remove(quad);
+    }
+    for (name, iri) in prefixes {
+        let prefix = Literal::new_simple_literal(format!("{}={}", name, iri));
+        transaction.insert(QuadRef::new(graph, PREFIX, &prefix, METADATA_GRAPH));
+    }
+    Ok(())
+}
+
+/// Read `quads` with the horned-owl RDF reader, written to it as RDF/XML
+/// by [`TripleReader`].
+fn read_quads<I: Iterator<Item = Result<Quad, StorageError>>>(
+    quads: I,
+    config: ParserConfiguration,
+) -> Result<
+    (
+        ConcreteRDFOntology<RcStr, RcAnnotatedComponent>,
+        IncompleteParse<RcStr>,
+    ),
+    HornedOxiError,
+> {
+    let mut reader = TripleReader::new(quads);
+    let ontology = rdf::reader::read(&mut reader, config)?;
+    reader.finish()?;
+    Ok(ontology)
+}
+
+/// A term of the horned-owl RDF reader as an RDF term. Each blank node is
+/// given a fresh one the first time it is met, kept in `bnodes`.
+fn rdf_term<A: ForIRI>(
+    term: &reader::Term<A>,
+    bnodes: &mut HashMap<reader::BNode<A>, BlankNode>,
+) -> Term {
+    let iri = match term {
+        reader::Term::OWL(v) => v.as_ref(),
+        reader::Term::RDF(v) => v.as_ref(),
+        reader::Term::RDFS(v) => v.as_ref(),
+        reader::Term::SWRL(v) => v.as_ref(),
+        reader::Term::FacetTerm(v) => v.as_ref(),
+        reader::Term::Iri(iri) => iri.as_ref(),
+        reader::Term::BNode(bnode) => {
+            return bnodes.entry(bnode.clone()).or_default().clone().into();
+        }
+        reader::Term::Literal(literal) => {
+            return match literal {
+                horned_owl::model::Literal::Simple { literal } => {
+                    Literal::new_simple_literal(literal)
+                }
+                horned_owl::model::Literal::Language { literal, lang } => {
+                    Literal::new_language_tagged_literal_unchecked(literal, lang)
+                }
+                horned_owl::model::Literal::Datatype {
+                    literal,
+                    datatype_iri,
+                } => Literal::new_typed_literal(
+                    literal,
+                    NamedNode::new_unchecked(datatype_iri.as_ref()),
+                ),
+            }
+            .into();
+        }
+    };
+    NamedNode::new_unchecked(iri).into()
+}
+
+/// A triple of the horned-owl RDF reader as an RDF triple, if it is one.
+fn rdf_triple<A: ForIRI>(
+    triple: &[reader::Term<A>; 3],
+    bnodes: &mut HashMap<reader::BNode<A>, BlankNode>,
+) -> Option<Triple> {
+    let subject = match rdf_term(&triple[0], bnodes) {
+        Term::NamedNode(iri) => NamedOrBlankNode::from(iri),
+        Term::BlankNode(bnode) => bnode.into(),
+        _ => return None,
+    };
+    let Term::NamedNode(predicate) = rdf_term(&triple[1], bnodes) else {
+        return None;
+    };
+    Some(Triple::new(
+        subject,
+        predicate,
+        rdf_term(&triple[2], bnodes),
+    ))
+}
+
+/// The absolute `file:` IRI of `path`, which is also the name of the graph
+/// the file is loaded into by default. `..` is resolved lexically, without
+/// following symbolic links, so that one file has one graph name.
+pub fn file_iri(path: &Path) -> Result<String, HornedOxiError> {
+    let mut iri = String::from("file://");
+    let mut root = iri.len();
+    for component in std::path::absolute(path)?.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                iri.push('/');
+                percent_encode(&mut iri, prefix.as_os_str());
+                root = iri.len();
+            }
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir => {
+                // segments are percent-encoded, so `/` only separates them
+                if let Some(end) = iri[root..].rfind('/') {
+                    iri.truncate(root + end);
+                }
+            }
+            Component::Normal(segment) => {
+                iri.push('/');
+                percent_encode(&mut iri, segment);
+            }
+        }
+    }
+    Ok(iri)
+}
+
+/// Append `segment` to `iri`, percent-encoding every byte not allowed in
+/// an IRI path segment. Non-UTF-8 names are encoded byte by byte.
+fn percent_encode(iri: &mut String, segment: &std::ffi::OsStr) {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(segment).to_vec();
+    #[cfg(not(unix))]
+    let bytes = segment.to_string_lossy().into_owned().into_bytes();
+    for byte in bytes {
+        match byte {
+            b'A'..=b'Z'
+            | b'a'..=b'z'
+            | b'0'..=b'9'
+            | b'-'
+            | b'.'
+            | b'_'
+            | b'~'
+            | b'!'
+            | b'$'
+            | b'&'
+            | b'\''
+            | b'('
+            | b')'
+            | b'*'
+            | b'+'
+            | b','
+            | b';'
+            | b'='
+            | b':'
+            | b'@' => iri.push(byte as char),
+            _ => iri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+}
+
+pub fn parser_from_format(path: &Path, lenient: bool) -> Result<PreparedParser, HornedOxiError> {
+    parser_for_graph(path, NamedNodeRef::new(&file_iri(path)?)?, None, lenient)
+}
+
+/// Candidate types for `bytes`, most likely first, judged from the first
+/// non-blank bytes.
+pub fn sniff_types(bytes: &[u8]) -> Vec<ResourceType> {
+    let text = String::from_utf8_lossy(&bytes[..bytes.len().min(4096)]);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    let starts_with = |prefix: &str| {
+        text.get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+    };
+    if text.starts_with("Prefix(") || text.starts_with("Ontology(") {
+        vec![ResourceType::OFN]
+    } else if starts_with("@prefix") || starts_with("@base") {
+        vec![ResourceType::TTL, ResourceType::TriG, ResourceType::N3]
+    } else if starts_with("PREFIX") || starts_with("BASE") {
+        vec![ResourceType::TTL, ResourceType::TriG]
+    } else if text.starts_with('{') || text.starts_with('[') {
+        vec![ResourceType::JsonLd]
+    } else if text.starts_with("<?xml") || text.starts_with("<!") {
+        match xml_root(text) {
+            Some("Ontology") => vec![ResourceType::OWX, ResourceType::OWL],
+            _ => vec![ResourceType::OWL, ResourceType::OWX],
+        }
+    } else if text.starts_with('<') {
+        match xml_root(text) {
+            Some("Ontology") => vec![ResourceType::OWX, ResourceType::OWL],
+            Some("RDF") => vec![ResourceType::OWL, ResourceType::OWX],
+            // An IRI, as in N-Triples or Turtle.
+            _ => vec![
+                ResourceType::NTriples,
+                ResourceType::TTL,
+                ResourceType::NQuads,
+                ResourceType::TriG,
+                ResourceType::N3,
+            ],
+        }
+    } else {
+        vec![
+            ResourceType::TTL,
+            ResourceType::OFN,
+            ResourceType::OWL,
+            ResourceType::OWX,
+            ResourceType::NTriples,
+            ResourceType::NQuads,
+            ResourceType::TriG,
+            ResourceType::N3,
+            ResourceType::JsonLd,
+        ]
+    }
+}
+
+/// The local name of the root element of an XML document.
+fn xml_root(text: &str) -> Option<&str> {
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = &comment[comment.find("-->")? + 3..];
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = &rest[rest.find('>')? + 1..];
+        } else {
+            let name = rest.strip_prefix('<')?;
+            let end = name.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+            let name = &name[..end];
+            // An IRI in angle brackets is not an element name.
+            if name.contains("//") {
+                return None;
+            }
+            return Some(name.rsplit(':').next().unwrap_or(name));
+        }
+    }
+}
+
+/// A parser for `path` putting the default graph of the file into `graph`.
+/// Relative IRIs resolve against `base_iri`, or the file IRI of `path`.
+/// The extension is only trusted as far as the content agrees with it.
+pub fn parser_for_graph(
+    path: &Path,
+    graph: NamedNodeRef<'_>,
+    base_iri: Option<&str>,
+    lenient: bool,
+) -> Result<PreparedParser, HornedOxiError> {
+    parser_for_file_content(path, std::fs::read(path)?, graph, base_iri, lenient)
+}
+
+/// As [`parser_for_graph`], for the content `bytes` already read from
+/// `path`.
+fn parser_for_file_content(
+    path: &Path,
+    bytes: Vec<u8>,
+    graph: NamedNodeRef<'_>,
+    base_iri: Option<&str>,
+    lenient: bool,
+) -> Result<PreparedParser, HornedOxiError> {
+    if is_archive(path) {
+        return Err(HornedOxiErrorKind::InvalidInput(format!(
+            "{} is an archive, load it with insert_archive",
+            path.display()
+        ))
+        .into());
+    }
+    let base_iri = match base_iri {
+        Some(base_iri) => base_iri.to_string(),
+        None => file_iri(path)?,
+    };
+    parser_for_detected_input(
+        ParserInput::File(decompress(bytes, path)?),
+        path_type(path),
+        graph,
+        Some(&base_iri),
+        lenient,
+    )
+}
+
+/// A parser for `input` of unknown or uncertain type. The types sniffed
+/// from the content are candidates, `hint` first if the content allows it
+/// and last otherwise. The parser is for the first candidate; should the
+/// input not read as it when loaded, the next is tried, and so on. OFN and
+/// OWX are converted here, so candidates that fail to convert are passed
+/// over at once. Fails with the error of the first candidate.
+pub fn parser_for_detected_input(
+    input: ParserInput,
+    hint: Option<ResourceType>,
+    graph: NamedNodeRef<'_>,
+    base_iri: Option<&str>,
+    lenient: bool,
+) -> Result<PreparedParser, HornedOxiError> {
+    let mut candidates = sniff_types(input.as_slice());
+    if let Some(hint) = hint {
+        // `.rdf` and `.owl` are both RDF/XML.
+        let same = |candidate: &ResourceType| {
+            *candidate == hint || (hint == ResourceType::RDF && *candidate == ResourceType::OWL)
+        };
+        match candidates.iter().position(same) {
+            Some(position) => {
+                candidates.remove(position);
+                candidates.insert(0, hint);
+            }
+            None => candidates.push(hint),
+        }
+    }
+    prepare_candidates(input, candidates, graph, base_iri, lenient)
+}
+
+/// A parser for the first of `candidates` that `input` can be prepared
+/// for, keeping the rest to fall back on.
+fn prepare_candidates(
+    input: ParserInput,
+    mut candidates: Vec<ResourceType>,
+    graph: NamedNodeRef<'_>,
+    base_iri: Option<&str>,
+    lenient: bool,
+) -> Result<PreparedParser, HornedOxiError> {
+    let mut first_error = None;
+    while !candidates.is_empty() {
+        let resource_type = candidates.remove(0);
+        let converted = match resource_type {
+            ResourceType::OFN | ResourceType::OWX => {
+                to_rdf_xml(input.as_slice(), resource_type).map(Some)
+            }
+            _ => Ok(None),
+        };
+        let prepared = converted.and_then(|converted| {
+            Ok((
+                make_parser(resource_type, graph, base_iri, lenient)?,
+                converted,
+            ))
+        });
+        match prepared {
+            Ok((parser, converted)) => {
+                let (input, source, prefixes) = match converted {
+                    Some((buf, prefixes)) => {
+                        (ParserInput::Buffer(Cursor::new(buf)), Some(input), prefixes)
+                    }
+                    None => (input, None, Vec::new()),
+                };
+                return Ok(PreparedParser {
+                    parser,
+                    input,
+                    resource_type,
+                    graph: graph.into_owned(),
+                    prefixes,
+                    fallback: Fallback {
+                        source,
+                        candidates,
+                        base_iri: base_iri.map(str::to_string),
+                        lenient,
+                    },
+                });
+            }
+            Err(error) => {
+                first_error.get_or_insert(error);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| {
+        HornedOxiErrorKind::InvalidInput("Could not detect the input type".to_string()).into()
+    }))
+}
+
+/// A parser for `input` of type `resource_type`, putting its default graph
+/// into `graph`. OFN and OWX are converted to RDF/XML with horned-owl first.
+pub fn parser_for_input(
+    input: ParserInput,
+    resource_type: ResourceType,
+    graph: NamedNodeRef<'_>,
+    base_iri: Option<&str>,
+    lenient: bool,
+) -> Result<PreparedParser, HornedOxiError> {
+    let parser = make_parser(resource_type, graph, base_iri, lenient)?;
+    let (input, prefixes) = match resource_type {
+        ResourceType::OFN | ResourceType::OWX => {
+            let (converted, prefixes) = to_rdf_xml(input.as_slice(), resource_type)?;
+            (ParserInput::Buffer(Cursor::new(converted)), prefixes)
+        }
+        _ => (input, Vec::new()),
+    };
+    Ok(PreparedParser {
+        parser,
+        input,
+        resource_type,
+        graph: graph.into_owned(),
+        prefixes,
+        fallback: Fallback::default(),
+    })
 }
 
-pub fn path_type(path: &Path) -> Option<ResourceType> {
-    match path.extension().and_then(|s| s.to_str()) {
-        Some("ofn") => Some(ResourceType::OFN),
-        Some("owx") => Some(ResourceType::OWX),
-        Some("rdf") => Some(ResourceType::RDF),
-        Some("owl") => Some(ResourceType::OWL),
-        Some("ttl") => Some(ResourceType::TTL),
-        Some("nt") => Some(ResourceType::NTriples),
-        Some("nq") => Some(ResourceType::NQuads),
-        Some("trig") => Some(ResourceType::TriG),
-        Some("jsonld") => Some(ResourceType::JsonLd),
-        Some("n3") => Some(ResourceType::N3),
-        _ => None,
-    }
+fn prefix_list<'a>(prefixes: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<(String, String)> {
+    prefixes
+        .map(|(name, iri)| (name.to_string(), iri.to_string()))
+        .collect()
 }
-pub fn parser_from_format(path: &Path, lenient: bool) -> Result<PreparedParser, HornedOxiError> {
-    let make_parser = |fmt| {
-        let path_str = path.to_str().unwrap();
-        // TODO: Handle non default graph
-        let parser = RdfParser::from_format(fmt)
-            .with_default_graph(NamedNode::new(format!("file:://{}", path_str)).unwrap());
-        if lenient { parser.lenient() } else { parser }
-    };
-    let t_pat = path_type(path);
-    let prepared = match t_pat {
-        Some(ResourceType::OFN) => {
-            let file = File::open(path)?;
-            let mut reader = BufReader::new(file);
-            let (ont, _): (RcComponentMappedOntology, _) =
-                ofn::reader::read(&mut reader, ParserConfiguration::default())?;
-
-            let mut buf = Vec::new();
+
+/// Prefix names with the IRIs they stand for.
+type Prefixes = Vec<(String, String)>;
+
+/// Convert functional syntax or OWL/XML to RDF/XML with horned-owl, along
+/// with the prefixes it declares.
+fn to_rdf_xml(
+    mut bytes: &[u8],
+    resource_type: ResourceType,
+) -> Result<(Vec<u8>, Prefixes), HornedOxiError> {
+    let mut buf = Vec::new();
+    let mapping = match resource_type {
+        ResourceType::OFN => {
+            let (ont, mapping): (RcComponentMappedOntology, _) =
+                ofn::reader::read(&mut bytes, ParserConfiguration::default())?;
             rdf::writer::write(&mut buf, &ont)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::RdfXml),
-                input: ParserInput::Buffer(Cursor::new(buf)),
-            })
+            Some(mapping)
         }
-        Some(ResourceType::OWX) => {
-            let file = File::open(path)?;
-            let mut reader = BufReader::new(file);
+        ResourceType::OWX => {
             let ontology = owx::reader::read::<
                 RcStr,
                 ConcreteRDFOntology<RcStr, RcAnnotatedComponent>,
                 _,
-            >(&mut reader, ParserConfiguration::default())?;
-
-            let mut buf = Vec::new();
+            >(&mut bytes, ParserConfiguration::default())?;
             rdf::writer::write(&mut buf, &ontology.0.into())?;
-
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::RdfXml),
-                input: ParserInput::Buffer(Cursor::new(buf)),
-            })
-        }
-        Some(ResourceType::TTL) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::Turtle),
-                input,
-            })
-        }
-        Some(ResourceType::NTriples) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::NTriples),
-                input,
-            })
-        }
-        Some(ResourceType::NQuads) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::NQuads),
-                input,
-            })
-        }
-        Some(ResourceType::TriG) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::TriG),
-                input,
-            })
-        }
-        Some(ResourceType::JsonLd) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::JsonLd {
-                    profile: JsonLdProfileSet::default(),
-                }),
-                input,
-            })
+            Some(ontology.1)
         }
-        Some(ResourceType::N3) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::N3),
-                input,
-            })
-        }
-        Some(ResourceType::OWL) => {
-            let input = ParserInput::from_path(path)?;
-            Ok(PreparedParser {
-                parser: make_parser(RdfFormat::RdfXml),
-                input,
-            })
+        _ => {
+            buf.extend_from_slice(bytes);
+            None
         }
-        _ => Err(HornedOxiErrorKind::InvalidInput(format!(
-            "Unsupported parser: {}",
-            path.display()
-        ))),
     };
-    Ok(prepared?)
+    let prefixes = mapping
+        .iter()
+        .flat_map(|mapping| mapping.mappings())
+        .map(|(name, iri)| (name.clone(), iri.clone()))
+        .collect();
+    Ok((buf, prefixes))
+}
+
+/// The RDF syntax `resource_type` is read from. OFN and OWX are read from
+/// the RDF/XML they are converted to.
+fn rdf_format(resource_type: ResourceType) -> RdfFormat {
+    match resource_type {
+        ResourceType::OFN | ResourceType::OWX | ResourceType::RDF | ResourceType::OWL => {
+            RdfFormat::RdfXml
+        }
+        ResourceType::TTL => RdfFormat::Turtle,
+        ResourceType::NTriples => RdfFormat::NTriples,
+        ResourceType::NQuads => RdfFormat::NQuads,
+        ResourceType::TriG => RdfFormat::TriG,
+        ResourceType::JsonLd => RdfFormat::JsonLd {
+            profile: JsonLdProfileSet::default(),
+        },
+        ResourceType::N3 => RdfFormat::N3,
+    }
+}
+
+fn make_parser(
+    resource_type: ResourceType,
+    graph: NamedNodeRef<'_>,
+    base_iri: Option<&str>,
+    lenient: bool,
+) -> Result<RdfParser, HornedOxiError> {
+    let mut parser = RdfParser::from_format(rdf_format(resource_type)).with_default_graph(graph);
+    if let Some(base_iri) = base_iri {
+        parser = parser.with_base_iri(base_iri)?;
+    }
+    if lenient {
+        parser = parser.lenient();
+    }
+    Ok(parser)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_util::TempDir;
     use test_generator::test_resources;
 
     #[test_resources("data/owl-functional/*.ofn")]
@@ -311,6 +2108,702 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_imports_closure() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let path = Path::new("data/owl-functional/withimport/import-property.ofn");
+        let closure = store
+            .insert_file_with_imports(path, false, &Catalog::for_ontology(path).unwrap())
+            .unwrap();
+        let iris = closure
+            .graphs
+            .iter()
+            .map(|(iri, _)| iri.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            iris,
+            vec![
+                "http://www.example.com/iri",
+                "http://www.example.com/other-property"
+            ]
+        );
+        assert!(closure.missing.is_empty());
+        assert!(closure.cycles.is_empty());
+        let ontology = store.get_ontology(closure.graphs[1].1.clone()).unwrap();
+        let extract = store.convert_ontology(ontology).unwrap();
+        assert!(
+            extract
+                .iricache()
+                .contains_key("http://www.example.com/other-property#other-o")
+        );
+    }
+
+    #[test]
+    fn test_imports_missing() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let path = Path::new("data/owl-functional/withimport/import-property.ofn");
+        let closure = store
+            .insert_file_with_imports(path, false, &Catalog::new())
+            .unwrap();
+        assert_eq!(closure.graphs.len(), 1);
+        assert_eq!(
+            closure.missing,
+            vec!["http://www.example.com/other-property"]
+        );
+    }
+
+    #[test]
+    fn test_imports_cycle() {
+        let directory = TempDir::new("import-cycle");
+        for (name, import) in [("a", "b"), ("b", "a")] {
+            std::fs::write(
+                directory.join(format!("{}.ofn", name)),
+                format!(
+                    "Ontology(<http://www.example.com/{}>\nImport(<http://www.example.com/{}>)\n)",
+                    name, import
+                ),
+            )
+            .unwrap();
+        }
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let closure = store
+            .insert_file_with_imports(
+                &directory.join("a.ofn"),
+                false,
+                &Catalog::new().with_directory(directory.to_path_buf()),
+            )
+            .unwrap();
+        assert_eq!(closure.graphs.len(), 2);
+        assert_eq!(
+            closure.cycles,
+            vec![vec![
+                "http://www.example.com/a",
+                "http://www.example.com/b",
+                "http://www.example.com/a"
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_imports_cycle_through_other_names() {
+        let directory = TempDir::new("import-cycle-names");
+        let mut catalog = Catalog::new();
+        for (name, declared, import) in [
+            (
+                "a",
+                "<http://www.example.com/a> <http://www.example.com/a/1.0>",
+                "b-alias",
+            ),
+            ("b", "<http://www.example.com/b>", "a/1.0"),
+        ] {
+            let path = directory.join(format!("{}.ofn", name));
+            std::fs::write(
+                &path,
+                format!(
+                    "Ontology({}\nImport(<http://www.example.com/{}>)\n)",
+                    declared, import
+                ),
+            )
+            .unwrap();
+            catalog.insert(format!("http://www.example.com/{}", name), path);
+        }
+        catalog.insert("http://www.example.com/b-alias", directory.join("b.ofn"));
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let closure = store
+            .insert_file_with_imports(&directory.join("a.ofn"), false, &catalog)
+            .unwrap();
+        assert_eq!(closure.graphs.len(), 2);
+        assert_eq!(
+            closure.cycles,
+            vec![vec![
+                "http://www.example.com/a",
+                "http://www.example.com/b",
+                "http://www.example.com/a/1.0"
+            ]]
+        );
+
+        // the same, with the import resolved to the file only
+        std::fs::write(
+            directory.join("b.ofn"),
+            "Ontology(<http://www.example.com/b>\nImport(<http://www.example.com/a-alias>)\n)",
+        )
+        .unwrap();
+        catalog.insert("http://www.example.com/a-alias", directory.join("a.ofn"));
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let closure = store
+            .insert_file_with_imports(&directory.join("a.ofn"), false, &catalog)
+            .unwrap();
+        assert_eq!(
+            closure.cycles,
+            vec![vec![
+                "http://www.example.com/a",
+                "http://www.example.com/b",
+                "http://www.example.com/a-alias"
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_graph_management() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let graph = "http://www.example.com/graph";
+        store
+            .insert_file_into(
+                Path::new("data/owl-functional/class.ofn"),
+                graph,
+                None,
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            store.list_ontologies().unwrap(),
+            vec![LoadedOntology {
+                graph: graph.to_string(),
+                iri: Some("http://www.example.com/iri".to_string()),
+                version_iri: Some("http://www.example.com/viri".to_string()),
+            }]
+        );
+
+        let other = Path::new("data/owl-functional/withimport/other-property.ofn");
+        store.replace_graph(other, graph, None, false).unwrap();
+        let ontologies = store.list_ontologies().unwrap();
+        assert_eq!(ontologies.len(), 1);
+        assert_eq!(
+            ontologies[0].iri.as_deref(),
+            Some("http://www.example.com/other-property")
+        );
+        let ontology = store.get_ontology(graph.to_string()).unwrap();
+        let extract = store.convert_ontology(ontology).unwrap();
+        assert!(
+            !extract
+                .iricache()
+                .contains_key("http://www.example.com/iri#C")
+        );
+
+        let renamed = "http://www.example.com/renamed";
+        store.rename_graph(graph, renamed, false).unwrap();
+        assert_eq!(store.list_ontologies().unwrap()[0].graph, renamed);
+        assert!(store.remove_graph(renamed).unwrap());
+        assert!(!store.remove_graph(renamed).unwrap());
+        assert!(store.list_ontologies().unwrap().is_empty());
+        assert!(store.session.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_rename_graph_conflicts() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let and = "http://www.example.com/and";
+        let class = "http://www.example.com/class";
+        let missing = "http://www.example.com/missing";
+        store
+            .insert_file_into(Path::new("data/owl-ttl/and.ttl"), and, None, false)
+            .unwrap();
+        store
+            .insert_file_into(Path::new("data/owl-ttl/class.ttl"), class, None, false)
+            .unwrap();
+
+        assert!(store.rename_graph(missing, class, false).is_err());
+        assert!(!store.contains_graph(missing).unwrap());
+
+        let before = store.session.len().unwrap();
+        assert!(store.rename_graph(and, class, false).is_err());
+        assert_eq!(store.session.len().unwrap(), before);
+        assert!(store.contains_graph(and).unwrap());
+
+        let prefixes = store.prefixes(and).unwrap();
+        store.rename_graph(and, class, true).unwrap();
+        assert!(!store.contains_graph(and).unwrap());
+        assert_eq!(store.prefixes(class).unwrap(), prefixes);
+        // no quads or metadata of the old `class` are left over
+        let expected = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        expected
+            .insert_file_into(Path::new("data/owl-ttl/and.ttl"), class, None, false)
+            .unwrap();
+        assert_eq!(
+            store.session.len().unwrap(),
+            expected.session.len().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_file_iri() {
+        let iri = file_iri(Path::new("data/owl-rdf/a b#c%.owl")).unwrap();
+        assert!(iri.starts_with("file:///"), "{}", iri);
+        assert!(iri.ends_with("/data/owl-rdf/a%20b%23c%25.owl"), "{}", iri);
+        assert!(NamedNode::new(&iri).is_ok());
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+            let path = Path::new(OsStr::from_bytes(b"/tmp/\xff.owl"));
+            assert_eq!(file_iri(path).unwrap(), "file:///tmp/%FF.owl");
+        }
+    }
+
+    #[test]
+    fn test_file_iri_parent_dir() {
+        assert_eq!(
+            file_iri(Path::new("data/owl-rdf/../owl-rdf/./a.owl")).unwrap(),
+            file_iri(Path::new("data/owl-rdf/a.owl")).unwrap()
+        );
+        #[cfg(unix)]
+        {
+            assert_eq!(
+                file_iri(Path::new("/tmp/a/../b/c.owl")).unwrap(),
+                "file:///tmp/b/c.owl"
+            );
+            assert_eq!(
+                file_iri(Path::new("/../tmp/c.owl")).unwrap(),
+                "file:///tmp/c.owl"
+            );
+        }
+    }
+
+    #[test]
+    fn test_base_iri() {
+        let directory = TempDir::new("base-iri");
+        let path = directory.join("relative.ttl");
+        std::fs::write(
+            &path,
+            "<> a <http://www.w3.org/2002/07/owl#Ontology> .\n\
+             <#A> a <http://www.w3.org/2002/07/owl#Class> .\n",
+        )
+        .unwrap();
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        store.insert_file(&path, false).unwrap();
+        let extract = store
+            .convert_ontology(store.get_file_ontology(&path).unwrap())
+            .unwrap();
+        let file = file_iri(&path).unwrap();
+        assert!(
+            extract
+                .iricache()
+                .contains_key(format!("{}#A", file).as_str())
+        );
+
+        let graph = "http://www.example.com/graph";
+        let base = "http://www.example.com/iri";
+        store
+            .insert_file_into(&path, graph, Some(base), false)
+            .unwrap();
+        let extract = store
+            .convert_ontology(store.get_ontology(graph.to_string()).unwrap())
+            .unwrap();
+        assert!(
+            extract
+                .iricache()
+                .contains_key("http://www.example.com/iri#A")
+        );
+        assert_eq!(
+            extract.ontology_iri().map(|iri| iri.to_string()),
+            Some(base.to_string())
+        );
+    }
+
+    #[test]
+    fn test_insert_bytes() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        for (resource, resource_type) in [
+            ("data/owl-functional/class.ofn", ResourceType::OFN),
+            ("data/owl-xml/class.owx", ResourceType::OWX),
+            ("data/owl-ttl/class.ttl", ResourceType::TTL),
+            ("data/owl-rdf/class.owl", ResourceType::OWL),
+        ] {
+            let graph = format!("http://www.example.com/{:?}", resource_type);
+            let bytes = std::fs::read(resource).unwrap();
+            store
+                .insert_bytes(&bytes, resource_type, &graph, None, false)
+                .unwrap();
+            let extract = store
+                .convert_ontology(store.get_ontology(graph).unwrap())
+                .unwrap();
+            assert!(
+                extract
+                    .iricache()
+                    .contains_key("http://www.example.com/iri#C"),
+                "{}",
+                resource
+            );
+        }
+        assert_eq!(store.list_ontologies().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_insert_reader_base_iri() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let turtle = "<> a <http://www.w3.org/2002/07/owl#Ontology> .";
+        let graph = "http://www.example.com/graph";
+        store
+            .insert_reader(
+                turtle.as_bytes(),
+                ResourceType::TTL,
+                graph,
+                Some("http://www.example.com/iri"),
+                false,
+            )
+            .unwrap();
+        assert_eq!(
+            store.list_ontologies().unwrap()[0].iri.as_deref(),
+            Some("http://www.example.com/iri")
+        );
+    }
+
+    #[test]
+    fn test_sniff_types() {
+        for (resource, resource_type) in [
+            ("data/owl-functional/class.ofn", ResourceType::OFN),
+            ("data/owl-xml/class.owx", ResourceType::OWX),
+            ("data/owl-ttl/class.ttl", ResourceType::TTL),
+            ("data/owl-rdf/class.owl", ResourceType::OWL),
+        ] {
+            let bytes = std::fs::read(resource).unwrap();
+            assert_eq!(sniff_types(&bytes)[0], resource_type, "{}", resource);
+        }
+        assert_eq!(
+            sniff_types(b"\xef\xbb\xbf <http://a.b/c> <http://a.b/d> <http://a.b/e> .")[0],
+            ResourceType::NTriples
+        );
+        assert_eq!(
+            sniff_types(b"  {\"@context\": {}}"),
+            vec![ResourceType::JsonLd]
+        );
+        assert_eq!(
+            sniff_types(b"<!-- comment --><Ontology xmlns=\"http://www.w3.org/2002/07/owl#\"/>")[0],
+            ResourceType::OWX
+        );
+    }
+
+    #[test]
+    fn test_detect_misleading_extension() {
+        let directory = TempDir::new("sniff");
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        for (resource, resource_type, name) in [
+            (
+                "data/owl-functional/class.ofn",
+                ResourceType::OFN,
+                "ofn.owl",
+            ),
+            ("data/owl-xml/class.owx", ResourceType::OWX, "owx.owl"),
+            ("data/owl-ttl/class.ttl", ResourceType::TTL, "ttl.owl"),
+            ("data/owl-rdf/class.owl", ResourceType::OWL, "owl.ttl"),
+            ("data/owl-ttl/class.ttl", ResourceType::TTL, "ttl"),
+        ] {
+            let path = directory.join(name);
+            std::fs::copy(resource, &path).unwrap();
+            assert_eq!(
+                store.insert_file(&path, false).unwrap(),
+                resource_type,
+                "{}",
+                name
+            );
+            let extract = store
+                .convert_ontology(store.get_file_ontology(&path).unwrap())
+                .unwrap();
+            assert!(
+                extract
+                    .iricache()
+                    .contains_key("http://www.example.com/iri#C"),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_falls_back_when_loading() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let graph = "http://www.example.com/fallback";
+        // Read as N-Triples first, which fails on the second line.
+        let turtle = b"<http://a.b/s> <http://a.b/p> <http://a.b/o> .
+<http://a.b/s> <http://a.b/p> [ <http://a.b/p> <http://a.b/o> ] .
+";
+        assert_eq!(sniff_types(turtle)[0], ResourceType::NTriples);
+        assert_eq!(
+            store
+                .replace_graph_with_bytes(turtle.to_vec(), None, graph, false)
+                .unwrap(),
+            ResourceType::TTL
+        );
+        let graph = NamedNodeRef::new(graph).unwrap();
+        assert_eq!(
+            store
+                .session
+                .quads_for_pattern(None, None, None, Some(graph.into()))
+                .count(),
+            3
+        );
+        assert!(
+            store
+                .replace_graph_with_bytes(b"<http://a.b/s> (".to_vec(), None, graph.as_str(), false)
+                .is_err()
+        );
+        assert_eq!(
+            store
+                .session
+                .quads_for_pattern(None, None, None, Some(graph.into()))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_failed_load_keeps_nothing() {
+        let directory = TempDir::new("failed-load");
+        let path = directory.join("broken.ttl");
+        std::fs::write(
+            &path,
+            "<http://a.b/s> <http://a.b/p> <http://a.b/o> .\n<http://a.b/s> (",
+        )
+        .unwrap();
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        assert!(store.insert_file(&path, false).is_err());
+        assert!(store.session.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_compressed_input() {
+        use std::io::Write;
+
+        let directory = TempDir::new("compressed");
+        let bytes = std::fs::read("data/owl-ttl/class.ttl").unwrap();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&bytes).unwrap();
+        let mut bzip = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bzip.write_all(&bytes).unwrap();
+        let zstd = ruzstd::encoding::compress_to_vec(
+            bytes.as_slice(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        for (name, compressed) in [
+            ("class.ttl.gz", gzip.finish().unwrap()),
+            ("class.ttl.bz2", bzip.finish().unwrap()),
+            ("class.ttl.zst", zstd),
+        ] {
+            let path = directory.join(name);
+            std::fs::write(&path, compressed).unwrap();
+            assert_eq!(path_type(&path), Some(ResourceType::TTL));
+            assert_eq!(store.insert_file(&path, false).unwrap(), ResourceType::TTL);
+            let extract = store
+                .convert_ontology(store.get_file_ontology(&path).unwrap())
+                .unwrap();
+            assert!(
+                extract
+                    .iricache()
+                    .contains_key("http://www.example.com/iri#C"),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_insert_archive() {
+        use std::io::Write;
+
+        let directory = TempDir::new("archive");
+        let path = directory.join("ontologies.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, resource) in [
+            ("class.ofn", "data/owl-functional/class.ofn"),
+            ("nested/and.owl", "data/owl-rdf/and.owl"),
+            ("README", "data/owl-ttl/class.ttl"),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(&std::fs::read(resource).unwrap()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        assert!(store.insert_file(&path, false).is_err());
+        let loaded = store.insert_archive(&path, false).unwrap();
+        let archive = file_iri(&path).unwrap();
+        assert_eq!(
+            loaded,
+            vec![
+                (format!("jar:{}!/class.ofn", archive), ResourceType::OFN),
+                (
+                    format!("jar:{}!/nested/and.owl", archive),
+                    ResourceType::OWL
+                ),
+            ]
+        );
+        assert_eq!(store.list_ontologies().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_insert_archive_failing_entry() {
+        use std::io::Write;
+
+        let directory = TempDir::new("archive-failing");
+        let path = directory.join("ontologies.zip");
+        let write_archive = |broken: bool| {
+            let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("class.ofn", options).unwrap();
+            zip.write_all(&std::fs::read("data/owl-functional/class.ofn").unwrap())
+                .unwrap();
+            if broken {
+                zip.start_file("broken.ttl", options).unwrap();
+                zip.write_all(b"this is not turtle <").unwrap();
+            }
+            zip.finish().unwrap();
+        };
+        let class = archive_entry_iri(&path, Path::new("class.ofn")).unwrap();
+        let class = NamedNodeRef::new(&class).unwrap();
+
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        write_archive(true);
+        assert!(store.insert_archive(&path, false).is_err());
+        assert!(store.list_ontologies().unwrap().is_empty());
+        assert!(!store.session.contains_named_graph(class).unwrap());
+
+        // A failing archive leaves graphs loaded before it untouched.
+        write_archive(false);
+        store.insert_archive(&path, false).unwrap();
+        let quads = store
+            .session
+            .quads_for_pattern(None, None, None, Some(class.into()))
+            .count();
+        write_archive(true);
+        assert!(store.insert_archive(&path, false).is_err());
+        assert_eq!(
+            store
+                .session
+                .quads_for_pattern(None, None, None, Some(class.into()))
+                .count(),
+            quads
+        );
+        assert_eq!(store.list_ontologies().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_read_at_most() {
+        use std::io::Write;
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&[0; 1024]).unwrap();
+        let gz = gz.finish().unwrap();
+        let name = Path::new("zeros.ttl.gz");
+        assert_eq!(
+            decompress_at_most(gz.clone(), name, 1024).unwrap().len(),
+            1024
+        );
+        assert!(decompress_at_most(gz, name, 1023).is_err());
+        assert!(read_at_most(&[0u8; 10][..], 9).is_err());
+        assert_eq!(read_at_most(&[0u8; 10][..], 10).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_insert_file_if_changed() {
+        let directory = TempDir::new("changed");
+        let path = directory.join("class.ttl");
+        std::fs::copy("data/owl-ttl/class.ttl", &path).unwrap();
+        let graph = file_iri(&path).unwrap();
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        assert_eq!(
+            store.insert_file_if_changed(&path, false).unwrap(),
+            Some(ResourceType::TTL)
+        );
+        let hash = store.content_hash(&graph).unwrap().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(store.insert_file_if_changed(&path, false).unwrap(), None);
+        assert_eq!(store.list_ontologies().unwrap().len(), 1);
+
+        std::fs::copy("data/owl-ttl/and.ttl", &path).unwrap();
+        assert_eq!(
+            store.insert_file_if_changed(&path, false).unwrap(),
+            Some(ResourceType::TTL)
+        );
+        assert_ne!(store.content_hash(&graph).unwrap().unwrap(), hash);
+        let extract = store
+            .convert_ontology(store.get_file_ontology(&path).unwrap())
+            .unwrap();
+        assert!(
+            extract
+                .iricache()
+                .contains_key("http://www.example.com/iri#A")
+        );
+
+        assert!(store.remove_graph(&graph).unwrap());
+        assert_eq!(store.content_hash(&graph).unwrap(), None);
+        assert_eq!(
+            store.insert_file_if_changed(&path, false).unwrap(),
+            Some(ResourceType::TTL)
+        );
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[test]
+    fn test_reopen_store() {
+        let directory = TempDir::new("reopen");
+        let path = directory.join("store");
+        let file = Path::new("data/owl-ttl/class.ttl");
+        let graph = file_iri(file).unwrap();
+        {
+            let store = HornedOxiStore::<RcStr>::open(&path).unwrap();
+            assert_eq!(
+                store.insert_file_if_changed(file, false).unwrap(),
+                Some(ResourceType::TTL)
+            );
+        }
+
+        let store = HornedOxiStore::<RcStr>::open_read_only(&path).unwrap();
+        let graphs = store
+            .list_ontologies()
+            .unwrap()
+            .into_iter()
+            .map(|ontology| ontology.graph)
+            .collect::<Vec<_>>();
+        assert_eq!(graphs, vec![graph.clone()]);
+        let extract = store
+            .convert_ontology(store.get_file_ontology(file).unwrap())
+            .unwrap();
+        assert!(
+            extract
+                .iricache()
+                .contains_key("http://www.example.com/iri#C")
+        );
+        drop(store);
+
+        // The content hash is kept too.
+        let store = HornedOxiStore::<RcStr>::open(&path).unwrap();
+        assert_eq!(store.insert_file_if_changed(file, false).unwrap(), None);
+    }
+
+    #[cfg(feature = "rocksdb")]
+    #[test]
+    fn test_backup_and_restore() {
+        let directory = TempDir::new("backup");
+        let class = Path::new("data/owl-ttl/class.ttl");
+        let store = HornedOxiStore::<RcStr>::open(directory.join("store")).unwrap();
+        store.insert_file(class, false).unwrap();
+        store.backup(directory.join("backup")).unwrap();
+        // Changes after the backup are not part of it.
+        store
+            .insert_file(Path::new("data/owl-ttl/and.ttl"), false)
+            .unwrap();
+
+        let restored =
+            HornedOxiStore::<RcStr>::restore(directory.join("backup"), directory.join("restored"))
+                .unwrap();
+        let graphs = restored
+            .list_ontologies()
+            .unwrap()
+            .into_iter()
+            .map(|ontology| ontology.graph)
+            .collect::<Vec<_>>();
+        assert_eq!(graphs, vec![file_iri(class).unwrap()]);
+        assert_eq!(
+            restored.get_file_ontology(class).unwrap(),
+            store.get_file_ontology(class).unwrap()
+        );
+        assert!(
+            HornedOxiStore::<RcStr>::restore(directory.join("backup"), directory.join("restored"))
+                .is_err()
+        );
+    }
+
     fn pretty_print_quads(
         quads1: &[oxigraph::model::Quad],
         quads2: &[oxigraph::model::Quad],
@@ -344,4 +2837,306 @@ mod test {
 
         out
     }
+
+    #[test]
+    fn test_ontology_diagnostics() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let graph = "http://www.example.com/graph";
+        store
+            .insert_bytes(
+                br#"@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix : <http://www.example.com/iri#> .
+<http://www.example.com/iri> a owl:Ontology .
+:C a owl:Class .
+:D a owl:Class ; rdfs:subClassOf [ a owl:Restriction ; owl:someValuesFrom :C ] .
+"#,
+                ResourceType::TTL,
+                graph,
+                None,
+                false,
+            )
+            .unwrap();
+        let (ontology, diagnostics) = store
+            .get_ontology_with_diagnostics(graph.to_string())
+            .unwrap();
+        assert!(!diagnostics.is_empty());
+        assert!(
+            ontology
+                .iter()
+                .any(|c| matches!(&c.component, horned_owl::model::Component::DeclareClass(_)))
+        );
+
+        // The restriction is left over as a group of triples on one
+        // blank node, with the class it restricts to.
+        let c = Term::from(NamedNode::new_unchecked("http://www.example.com/iri#C"));
+        let restriction = diagnostics
+            .blank_nodes
+            .iter()
+            .find(|triples| triples.iter().any(|triple| triple.object == c))
+            .unwrap();
+        assert!(restriction.len() > 1);
+        assert!(
+            restriction
+                .iter()
+                .all(|triple| triple.subject == restriction[0].subject)
+        );
+    }
+
+    #[test]
+    fn test_export_ontology() {
+        use oxigraph::model::{Graph, dataset::CanonicalizationAlgorithm};
+
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let graph = "http://www.example.com/and";
+        store
+            .insert_file_into(Path::new("data/owl-ttl/and.ttl"), graph, None, false)
+            .unwrap();
+        assert!(
+            store
+                .prefixes(graph)
+                .unwrap()
+                .contains(&("o".to_string(), "http://www.example.com/iri#".to_string()))
+        );
+        let mut expected = store
+            .session
+            .quads_for_pattern(
+                None,
+                None,
+                None,
+                Some(NamedNodeRef::new(graph).unwrap().into()),
+            )
+            .map(|quad| Triple::from(quad.unwrap()))
+            .collect::<Graph>();
+        expected.canonicalize(CanonicalizationAlgorithm::Unstable);
+        for resource_type in [
+            ResourceType::OFN,
+            ResourceType::OWX,
+            ResourceType::RDF,
+            ResourceType::OWL,
+            ResourceType::TTL,
+            ResourceType::NTriples,
+            ResourceType::NQuads,
+            ResourceType::TriG,
+            ResourceType::JsonLd,
+            ResourceType::N3,
+        ] {
+            let mut bytes = Vec::new();
+            store
+                .export_ontology(graph, resource_type, &mut bytes)
+                .unwrap();
+            if let ResourceType::OFN | ResourceType::OWX = resource_type {
+                let exported = format!("http://www.example.com/{:?}", resource_type);
+                store
+                    .insert_bytes(&bytes, resource_type, &exported, None, false)
+                    .unwrap();
+                assert_eq!(
+                    store.get_ontology(exported).unwrap(),
+                    store.get_ontology(graph.to_string()).unwrap(),
+                    "{:?}",
+                    resource_type
+                );
+            } else {
+                let format = rdf_format(resource_type);
+                let mut exported = Graph::new();
+                for quad in RdfParser::from_format(format).for_slice(&bytes) {
+                    let quad = quad.unwrap();
+                    // Dataset syntaxes keep the name of the graph.
+                    assert_eq!(
+                        quad.graph_name.is_default_graph(),
+                        !format.supports_datasets(),
+                        "{:?}",
+                        resource_type
+                    );
+                    exported.insert(&Triple::from(quad));
+                }
+                exported.canonicalize(CanonicalizationAlgorithm::Unstable);
+                assert_eq!(exported, expected, "{:?}", resource_type);
+            }
+            if resource_type == ResourceType::TTL {
+                let turtle = String::from_utf8(bytes).unwrap();
+                assert!(turtle.contains("@prefix o: <http://www.example.com/iri#>"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_vowl_json_cache() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let graph = "http://www.example.com/cached";
+        store
+            .insert_file_into(Path::new("data/owl-ttl/and.ttl"), graph, None, false)
+            .unwrap();
+        let first = store.vowl_json(graph).unwrap();
+        assert!(Arc::ptr_eq(&first, &store.vowl_json(graph).unwrap()));
+
+        store
+            .replace_graph(Path::new("data/owl-ttl/class.ttl"), graph, None, false)
+            .unwrap();
+        let replaced = store.vowl_json(graph).unwrap();
+        assert!(!Arc::ptr_eq(&first, &replaced));
+        assert!(!replaced.contains("http://www.example.com/iri#D"));
+
+        store
+            .rename_graph(graph, "http://www.example.com/renamed", false)
+            .unwrap();
+        assert!(store.vowl_json(graph).is_err());
+        assert!(
+            store
+                .extract_graph(graph, ExtractConfiguration::default())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_declared_namespace_is_internal() {
+        let directory = TempDir::new("declared-namespace");
+        let path = directory.join("onto.ofn");
+        std::fs::write(
+            &path,
+            "Prefix(:=<http://ex.org/>)
+Prefix(foaf:=<http://xmlns.com/foaf/0.1/>)
+Ontology(<http://ex.org/onto.owl>
+Declaration(Class(:A))
+Declaration(Class(foaf:Person))
+SubClassOf(:A foaf:Person)
+)",
+        )
+        .unwrap();
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        store.insert_file(&path, false).unwrap();
+        let graph = file_iri(&path).unwrap();
+        for extract in [
+            store
+                .extract_ontology(&graph, ExtractConfiguration::default())
+                .unwrap(),
+            store
+                .extract_graph(&graph, ExtractConfiguration::default())
+                .unwrap(),
+        ] {
+            assert!(!extract.is_external("http://ex.org/A"));
+            assert!(extract.is_external("http://xmlns.com/foaf/0.1/Person"));
+            let vowl = extract.to_vowl();
+            assert!(
+                vowl.namespace
+                    .iter()
+                    .any(|ns| ns.name == "foaf" && ns.iri == "http://xmlns.com/foaf/0.1/")
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_and_update() {
+        let store = HornedOxiStore::<RcStr>::new(Store::new().unwrap());
+        let and = "http://www.example.com/and";
+        let class = "http://www.example.com/class";
+        store
+            .insert_file_into(Path::new("data/owl-ttl/and.ttl"), and, None, false)
+            .unwrap();
+        store
+            .insert_file_into(Path::new("data/owl-ttl/class.ttl"), class, None, false)
+            .unwrap();
+        let classes = "PREFIX owl: <http://www.w3.org/2002/07/owl#>
+            SELECT DISTINCT ?c WHERE { ?c a owl:Class FILTER isIRI(?c) }";
+        let count = |scope| match store.query(classes, scope).unwrap() {
+            QueryResult::Solutions {
+                variables,
+                solutions,
+            } => {
+                assert_eq!(variables, vec![Variable::new_unchecked("c")]);
+                solutions.len()
+            }
+            result => panic!("{:?}", result),
+        };
+        assert_eq!(count(GraphScope::Graph(and)), 4);
+        assert_eq!(count(GraphScope::Graph(class)), 1);
+        assert_eq!(count(GraphScope::Union), 4);
+
+        let ask = "ASK { <http://www.example.com/iri#D> ?p ?o }";
+        assert_eq!(
+            store.query(ask, GraphScope::Graph(class)).unwrap(),
+            QueryResult::Boolean(false)
+        );
+        let QueryResult::Graph(triples) = store
+            .query(
+                "CONSTRUCT { ?s ?p ?o } WHERE { ?s ?p ?o }",
+                GraphScope::Graph(class),
+            )
+            .unwrap()
+        else {
+            panic!("Expected triples");
+        };
+        assert!(!triples.is_empty());
+        // The metadata graph is not part of the union.
+        assert_eq!(
+            store
+                .query(
+                    "ASK { GRAPH ?g { ?s <urn:x-horned-oxi:prefix> ?o } }",
+                    GraphScope::Union
+                )
+                .unwrap(),
+            QueryResult::Boolean(false)
+        );
+
+        let cached = store.vowl_json(and).unwrap();
+        let insert = "INSERT DATA {
+            <http://www.example.com/iri#E> a <http://www.w3.org/2002/07/owl#Class> }";
+        assert_eq!(
+            store.update(insert, GraphScope::Graph(and)).unwrap(),
+            vec![and.to_string()]
+        );
+        let updated = store.vowl_json(and).unwrap();
+        assert!(!Arc::ptr_eq(&cached, &updated));
+        assert!(updated.contains("http://www.example.com/iri#E"));
+        assert_eq!(count(GraphScope::Graph(and)), 5);
+
+        // Writes must stay within the scope and name their graph over the
+        // union.
+        assert!(store.update(insert, GraphScope::Union).is_err());
+        let into_class = format!(
+            "INSERT DATA {{ GRAPH <{}> {{ <urn:a> <urn:b> <urn:c> }} }}",
+            class
+        );
+        assert!(store.update(&into_class, GraphScope::Graph(and)).is_err());
+        assert_eq!(
+            store.update(&into_class, GraphScope::Union).unwrap(),
+            vec![class.to_string()]
+        );
+
+        let mut changed = store
+            .update(
+                "DELETE { GRAPH ?g { ?c ?p ?o } } WHERE {
+                    GRAPH ?g { ?c ?p ?o FILTER(?c = <http://www.example.com/iri#C>) } }",
+                GraphScope::Union,
+            )
+            .unwrap();
+        changed.sort();
+        assert_eq!(changed, vec![and.to_string(), class.to_string()]);
+        assert_eq!(count(GraphScope::Graph(class)), 0);
+
+        // A graph variable cannot be bound to a graph outside the scope.
+        let before = store.session.len().unwrap();
+        for scope in [GraphScope::Union, GraphScope::Graph(and)] {
+            for bind in [
+                format!("BIND(<{}> AS ?g)", METADATA_GRAPH.as_str()),
+                "VALUES ?g { <http://www.example.com/other> }".to_string(),
+            ] {
+                store
+                    .update(
+                        &format!(
+                            "INSERT {{ GRAPH ?g {{ <urn:a> <urn:b> <urn:c> }} }} WHERE {{ {} }}",
+                            bind
+                        ),
+                        scope,
+                    )
+                    .unwrap();
+            }
+        }
+        assert_eq!(store.session.len().unwrap(), before);
+        assert!(
+            !store
+                .contains_graph("http://www.example.com/other")
+                .unwrap()
+        );
+    }
 }